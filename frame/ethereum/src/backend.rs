@@ -0,0 +1,68 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EVM backend of the Ethereum pallet: the EVM pallet's backend, with `BLOCKHASH` answered
+//! from the Ethereum block hashes of this pallet.
+
+use sp_std::prelude::*;
+use ethereum_types::{H160, H256, U256};
+use evm::backend::{Apply, ApplyBackend, Backend as BackendT, Basic, Log};
+use pallet_evm::Vicinity;
+use crate::{Module, Trait};
+
+/// Backend of the executions of the Ethereum pallet.
+pub struct Backend<'vicinity, T>(pallet_evm::Backend<'vicinity, T>);
+
+impl<'vicinity, T: Trait> Backend<'vicinity, T> {
+	pub fn new(vicinity: &'vicinity Vicinity) -> Self {
+		Self(pallet_evm::Backend::new(vicinity))
+	}
+}
+
+impl<'vicinity, T: Trait> BackendT for Backend<'vicinity, T> {
+	fn gas_price(&self) -> U256 { self.0.gas_price() }
+	fn origin(&self) -> H160 { self.0.origin() }
+
+	fn block_hash(&self, number: U256) -> H256 {
+		if number > U256::from(u32::max_value()) {
+			return H256::default()
+		}
+		Module::<T>::block_hash(number.low_u32().into())
+	}
+
+	fn block_number(&self) -> U256 { self.0.block_number() }
+	fn block_coinbase(&self) -> H160 { self.0.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.0.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.0.block_difficulty() }
+	fn block_gas_limit(&self) -> U256 { self.0.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.0.chain_id() }
+	fn exists(&self, address: H160) -> bool { self.0.exists(address) }
+	fn basic(&self, address: H160) -> Basic { self.0.basic(address) }
+	fn code_hash(&self, address: H160) -> H256 { self.0.code_hash(address) }
+	fn code_size(&self, address: H160) -> usize { self.0.code_size(address) }
+	fn code(&self, address: H160) -> Vec<u8> { self.0.code(address) }
+	fn storage(&self, address: H160, index: H256) -> H256 { self.0.storage(address, index) }
+}
+
+impl<'vicinity, T: Trait> ApplyBackend for Backend<'vicinity, T> {
+	fn apply<A, I, L>(&mut self, values: A, logs: L, delete_empty: bool) where
+		A: IntoIterator<Item=Apply<I>>,
+		I: IntoIterator<Item=(H256, H256)>,
+		L: IntoIterator<Item=Log>,
+	{
+		self.0.apply(values, logs, delete_empty)
+	}
+}
@@ -0,0 +1,192 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-1559 dynamic fee transactions and the EIP-2718 envelope distinguishing them from
+//! legacy transactions.
+//!
+//! Only the encoding is provided so far: `transact` and `validate_unsigned` still take
+//! legacy transactions, as the chain has no base fee to validate dynamic fee
+//! transactions against and stored Ethereum blocks can only hold legacy transactions.
+
+use sp_std::prelude::*;
+use ethereum::TransactionAction;
+use ethereum_types::{H160, H256, U256};
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use sha3::{Digest, Keccak256};
+
+/// EIP-2718 type byte of dynamic fee transactions.
+pub const DYNAMIC_FEE_TRANSACTION_TYPE: u8 = 2;
+
+/// Storage slots of an address that a transaction declares it will access.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListItem {
+	pub address: H160,
+	pub storage_keys: Vec<H256>,
+}
+
+impl Encodable for AccessListItem {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.address);
+		s.append_list(&self.storage_keys);
+	}
+}
+
+impl Decodable for AccessListItem {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		Ok(Self {
+			address: rlp.val_at(0)?,
+			storage_keys: rlp.list_at(1)?,
+		})
+	}
+}
+
+/// Signed EIP-1559 transaction, paying the block's base fee plus a priority fee capped
+/// by `max_fee_per_gas`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynamicFeeTransaction {
+	pub chain_id: u64,
+	pub nonce: U256,
+	pub max_priority_fee_per_gas: U256,
+	pub max_fee_per_gas: U256,
+	pub gas_limit: U256,
+	pub action: TransactionAction,
+	pub value: U256,
+	pub input: Vec<u8>,
+	pub access_list: Vec<AccessListItem>,
+	pub odd_y_parity: bool,
+	pub r: H256,
+	pub s: H256,
+}
+
+impl DynamicFeeTransaction {
+	fn rlp_append_unsigned(&self, s: &mut RlpStream, fields: usize) {
+		s.begin_list(fields);
+		s.append(&self.chain_id);
+		s.append(&self.nonce);
+		s.append(&self.max_priority_fee_per_gas);
+		s.append(&self.max_fee_per_gas);
+		s.append(&self.gas_limit);
+		s.append(&self.action);
+		s.append(&self.value);
+		s.append(&self.input);
+		s.append_list(&self.access_list);
+	}
+
+	/// Hash signed by the sender.
+	pub fn signing_hash(&self) -> H256 {
+		let mut s = RlpStream::new();
+		self.rlp_append_unsigned(&mut s, 9);
+		let payload = s.out();
+		let mut message = Vec::with_capacity(payload.len() + 1);
+		message.push(DYNAMIC_FEE_TRANSACTION_TYPE);
+		message.extend_from_slice(&payload);
+		H256::from_slice(Keccak256::digest(&message).as_slice())
+	}
+
+	/// Gas price actually paid in a block with the given base fee, `None` if the maximum
+	/// fee does not cover it.
+	pub fn effective_gas_price(&self, base_fee: U256) -> Option<U256> {
+		crate::effective_gas_price(self.max_fee_per_gas, self.max_priority_fee_per_gas, base_fee)
+	}
+}
+
+impl Encodable for DynamicFeeTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		self.rlp_append_unsigned(s, 12);
+		s.append(&self.odd_y_parity);
+		s.append(&self.r);
+		s.append(&self.s);
+	}
+}
+
+impl Decodable for DynamicFeeTransaction {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 12 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		Ok(Self {
+			chain_id: rlp.val_at(0)?,
+			nonce: rlp.val_at(1)?,
+			max_priority_fee_per_gas: rlp.val_at(2)?,
+			max_fee_per_gas: rlp.val_at(3)?,
+			gas_limit: rlp.val_at(4)?,
+			action: rlp.val_at(5)?,
+			value: rlp.val_at(6)?,
+			input: rlp.val_at(7)?,
+			access_list: rlp.list_at(8)?,
+			odd_y_parity: rlp.val_at(9)?,
+			r: rlp.val_at(10)?,
+			s: rlp.val_at(11)?,
+		})
+	}
+}
+
+/// Transaction of any supported type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypedTransaction {
+	Legacy(ethereum::Transaction),
+	EIP1559(DynamicFeeTransaction),
+}
+
+impl TypedTransaction {
+	/// Encode as sent over the wire: legacy transactions as a plain RLP list, typed
+	/// transactions as their type byte followed by their RLP payload.
+	pub fn encode(&self) -> Vec<u8> {
+		match self {
+			TypedTransaction::Legacy(transaction) => rlp::encode(transaction),
+			TypedTransaction::EIP1559(transaction) => {
+				let payload = rlp::encode(transaction);
+				let mut bytes = Vec::with_capacity(payload.len() + 1);
+				bytes.push(DYNAMIC_FEE_TRANSACTION_TYPE);
+				bytes.extend_from_slice(&payload);
+				bytes
+			},
+		}
+	}
+
+	/// Decode a transaction encoded by `encode`.
+	pub fn decode(bytes: &[u8]) -> Result<Self, DecoderError> {
+		match bytes.first() {
+			None => Err(DecoderError::RlpIsTooShort),
+			Some(&first) if first >= 0xc0 => rlp::decode(bytes).map(TypedTransaction::Legacy),
+			Some(&DYNAMIC_FEE_TRANSACTION_TYPE) =>
+				rlp::decode(&bytes[1..]).map(TypedTransaction::EIP1559),
+			Some(_) => Err(DecoderError::Custom("unknown transaction type")),
+		}
+	}
+
+	/// Hash identifying the transaction, over its wire encoding.
+	pub fn hash(&self) -> H256 {
+		H256::from_slice(Keccak256::digest(&self.encode()).as_slice())
+	}
+
+	/// Gas price actually paid in a block with the given base fee, `None` if the
+	/// transaction does not cover it. Legacy transactions pay their gas price.
+	pub fn effective_gas_price(&self, base_fee: U256) -> Option<U256> {
+		match self {
+			TypedTransaction::Legacy(transaction) => crate::effective_gas_price(
+				transaction.gas_price,
+				transaction.gas_price,
+				base_fee,
+			),
+			TypedTransaction::EIP1559(transaction) => transaction.effective_gas_price(base_fee),
+		}
+	}
+}
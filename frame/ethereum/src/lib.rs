@@ -22,26 +22,97 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{decl_module, decl_storage, decl_error, decl_event, ensure, weights::Weight, traits::Get};
+use frame_support::{
+	decl_module, decl_storage, decl_error, decl_event, ensure, dispatch::DispatchResult, weights::Weight,
+	storage::IterableStorageMap, traits::{Get, FindAuthor},
+};
 use sp_std::prelude::*;
-use frame_system::{self as system, ensure_none};
-use ethereum_types::{H160, H64, H256, U256, Bloom};
+use frame_system::{self as system, ensure_none, ensure_signed};
+use pallet_evm::{ConvertAccountId, FeeCalculator, Precompiles, Vicinity, ExitReason, ExitError};
+use evm::{Config, CreateScheme, Handler, backend::ApplyBackend, executor::StackExecutor};
+use ethereum_types::{H160, H64, H256, H520, U256, Bloom, BloomInput};
 use sp_runtime::{
-	traits::UniqueSaturatedInto,
-	transaction_validity::{TransactionValidity, TransactionSource, ValidTransaction}
+	traits::{Convert, UniqueSaturatedInto, Zero, One},
+	transaction_validity::{
+		TransactionValidity, TransactionSource, ValidTransaction, InvalidTransaction,
+	},
 };
 use rlp;
 use sha3::{Digest, Keccak256};
+use codec::Encode;
 
-pub use frontier_rpc_primitives::TransactionStatus;
+pub use frontier_rpc_primitives::{
+	TransactionStatus, LegacyTransactionStatus, ExecutionError, MAX_BLOCKS_IN_RANGE, decode_v,
+	recover_signer,
+};
 pub use ethereum::{Transaction, Log, Block};
 
+mod backend;
+
+#[cfg(feature = "eip1559")]
+pub mod eip1559;
+
 #[cfg(all(feature = "std", test))]
 mod tests;
 
 #[cfg(all(feature = "std", test))]
 mod mock;
 
+/// Gas paid by every transaction.
+pub const G_TRANSACTION: u64 = 21000;
+/// Additional gas paid by contract creation transactions.
+pub const G_TXCREATE: u64 = 32000;
+/// Gas paid for every zero byte of transaction input.
+pub const G_TXDATAZERO: u64 = 4;
+/// Gas paid for every non-zero byte of transaction input.
+pub const G_TXDATANONZERO: u64 = 16;
+/// Gas paid for every 32-byte word of init code by contract creations (EIP-3860).
+pub const G_INITCODEWORD: u64 = 2;
+
+/// Layout of the values of `TransactionStatuses`: statuses with gas usage and log positions.
+pub const TRANSACTION_STATUS_LAYOUT: u32 = 1;
+
+/// Number of most recent Ethereum block hashes available to the `BLOCKHASH` opcode.
+pub const BLOCK_HASH_COUNT: u32 = 256;
+
+/// Configuration of the EVM executing transactions and calls.
+static EVM_CONFIG: Config = Config::istanbul();
+
+/// Keccak hash of empty code, the code hash of accounts without code.
+pub const EMPTY_CODE_HASH: [u8; 32] = [
+	0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+	0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+/// Custom error codes returned by transaction validation.
+#[repr(u8)]
+pub enum TransactionValidationError {
+	/// Gas limit is lower than the intrinsic gas of the transaction.
+	GasLimitTooLow = 0,
+	/// Transaction is not signed for the configured chain id.
+	InvalidChainId = 1,
+	/// Maximum cost of the transaction does not fit in 256 bits.
+	FeeOverflow = 2,
+	/// Maximum fee per gas of the transaction does not cover the base fee.
+	GasPriceTooLow = 3,
+	/// Gas limit of the transaction does not fit in 32 bits.
+	GasLimitTooHigh = 4,
+}
+
+/// Gas price paid by a transaction offering at most `max_fee_per_gas`, of which at most
+/// `max_priority_fee_per_gas` above the base fee, or `None` if it does not cover the base
+/// fee. Legacy transactions offer their gas price as both.
+pub fn effective_gas_price(
+	max_fee_per_gas: U256,
+	max_priority_fee_per_gas: U256,
+	base_fee: U256,
+) -> Option<U256> {
+	if max_fee_per_gas < base_fee {
+		return None
+	}
+	Some(max_fee_per_gas.min(base_fee.saturating_add(max_priority_fee_per_gas)))
+}
+
 /// A type alias for the balance type from this pallet's point of view.
 pub type BalanceOf<T> = <T as pallet_balances::Trait>::Balance;
 
@@ -54,6 +125,15 @@ pub trait Trait: frame_system::Trait<Hash=H256> + pallet_balances::Trait + palle
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
     type ChainId: Get<u64>;
+	/// Find the author of the current block, used as the Ethereum block beneficiary.
+	type FindAuthor: FindAuthor<H160>;
+	/// Number of blocks for which transaction statuses are kept. Zero keeps them forever.
+	type TransactionStatusRetention: Get<Self::BlockNumber>;
+	/// Maximum total gas limit of the Ethereum transactions in a block.
+	type BlockGasLimit: Get<U256>;
+	/// Finds the EVM pallet's events among the runtime events, to collect the logs of an
+	/// execution.
+	type EvmEvents: Convert<<Self as frame_system::Trait>::Event, Option<pallet_evm::Event<Self>>>;
 }
 
 decl_storage! {
@@ -64,12 +144,27 @@ decl_storage! {
 	// It is important to update your storage name so that your pallet's
 	// storage items are isolated from other pallets.
 	// ---------------------------------vvvvvvv
+	//
+	// The Ethereum block, transaction and hash indexes below are part of the runtime state
+	// of each block, so queries at a block only see its own ancestry and a reorg to another
+	// branch needs no explicit rollback.
 	trait Store for Module<T: Trait> as Example {
 		BlocksAndReceipts: map hasher(blake2_128_concat) H256 => Option<(ethereum::Block, Vec<ethereum::Receipt>)>;
 		BlockNumbers: map hasher(blake2_128_concat) T::BlockNumber => H256;
 		PendingTransactionsAndReceipts: Vec<(ethereum::Transaction, ethereum::Receipt)>;
 		TransactionStatuses: map hasher(blake2_128_concat) H256 => Option<TransactionStatus>;
+		/// Layout of the values of `TransactionStatuses`, zero for the `LegacyTransactionStatus`
+		/// layout chains stored before gas usage was tracked.
+		TransactionStatusLayout: u32;
 		Transactions: map hasher(blake2_128_concat) H256 => Option<(H256, u32)>;
+		BlockTransactionHashes: map hasher(blake2_128_concat) H256 => Option<Vec<H256>>;
+		/// Ring buffer of the last `BLOCK_HASH_COUNT` block hashes, indexed by block number
+		/// modulo `BLOCK_HASH_COUNT`.
+		BlockHashes: map hasher(twox_64_concat) u32 => Option<(T::BlockNumber, H256)>;
+		/// Substrate accounts that linked the address `T::ConvertAccountId` derives for them.
+		SubstrateAccounts get(fn substrate_account): map hasher(blake2_128_concat) H160 => Option<T::AccountId>;
+		/// Ethereum addresses claimed by Substrate accounts with `claim_address`.
+		EvmAddresses get(fn evm_address): map hasher(blake2_128_concat) T::AccountId => Option<H160>;
 	}
 }
 
@@ -124,6 +219,28 @@ decl_error! {
 	pub enum Error for Module<T: Trait> {
 		/// Transaction signed with wrong chain id
 		InvalidChainId,
+		/// Gas limit is lower than the intrinsic gas of the transaction
+		GasLimitTooLow,
+		/// Address is already linked to another Substrate account
+		AddressAlreadyLinked,
+		/// Maximum cost of the transaction does not fit in 256 bits
+		FeeOverflow,
+		/// Sender cannot pay the maximum cost of the transaction
+		InsufficientBalance,
+		/// Gas limit of the transaction does not fit in the rest of the block
+		BlockGasLimitExceeded,
+		/// Signature does not prove ownership of the claimed address
+		InvalidAddressSignature,
+		/// Account has already claimed an address
+		AddressAlreadyClaimed,
+		/// Gas price of the transaction is below the base fee
+		GasPriceTooLow,
+		/// Nonce of the transaction does not match the nonce of the sender
+		InvalidNonce,
+		/// Address the account controls before its claim still holds an EVM balance
+		PreviousAddressNotEmpty,
+		/// Gas limit of the transaction does not fit in 32 bits
+		GasLimitTooHigh,
 	}
 }
 
@@ -141,22 +258,87 @@ decl_module! {
 		fn transact(origin, transaction: ethereum::Transaction) {
 			ensure_none(origin)?;
 
+			let (chain_id, _) = decode_v(transaction.signature.v())
+				.ok_or("Invalid signature v value")?;
+			ensure!(chain_id == Some(T::ChainId::get()), Error::<T>::InvalidChainId);
 			ensure!(
-				transaction.signature.chain_id().unwrap_or_default() == T::ChainId::get(), 
-				Error::<T>::InvalidChainId
+				transaction.gas_limit >= Self::intrinsic_gas(&transaction),
+				Error::<T>::GasLimitTooLow
 			);
-			let mut sig = [0u8; 65];
-			let mut msg = [0u8; 32];
-			sig[0..32].copy_from_slice(&transaction.signature.r()[..]);
-			sig[32..64].copy_from_slice(&transaction.signature.s()[..]);
-			sig[64] = transaction.signature.standard_v();
-			msg.copy_from_slice(&transaction.message_hash(Some(T::ChainId::get()))[..]);
-
-			let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&sig, &msg)
-				.map_err(|_| "Recover public key failed")?;
-			let source = H160::from(H256::from_slice(Keccak256::digest(&pubkey).as_slice()));
-
-			Self::execute(source, transaction);
+			ensure!(
+				transaction.gas_limit <= U256::from(u32::max_value()),
+				Error::<T>::GasLimitTooHigh
+			);
+			ensure!(Self::fits_in_block(&transaction), Error::<T>::BlockGasLimitExceeded);
+			let (source, _) = recover_signer(&transaction)
+				.ok_or("Recover public key failed")?;
+			let max_cost = Self::max_cost(&transaction).ok_or(Error::<T>::FeeOverflow)?;
+			ensure!(
+				max_cost <= pallet_evm::Module::<T>::accounts(source).balance,
+				Error::<T>::InsufficientBalance
+			);
+			ensure!(
+				Self::effective_gas_price(&transaction).is_some(),
+				Error::<T>::GasPriceTooLow
+			);
+			// The pool only orders a sender's transactions; a block author may still include
+			// two with the same nonce.
+			ensure!(
+				transaction.nonce == pallet_evm::Module::<T>::accounts(source).nonce,
+				Error::<T>::InvalidNonce
+			);
+
+			Self::execute(source, transaction)?;
+		}
+
+		/// Record the caller as the Substrate account behind its converted Ethereum address,
+		/// so the address can be resolved back to it.
+		#[weight = 10_000]
+		fn link_account(origin) {
+			let who = ensure_signed(origin)?;
+			// A claimed address already resolves to the account; linking the converted one
+			// would map a second address back to it.
+			ensure!(!EvmAddresses::<T>::contains_key(&who), Error::<T>::AddressAlreadyClaimed);
+			let address = T::ConvertAccountId::convert_account_id(&who);
+			if let Some(linked) = SubstrateAccounts::<T>::get(address) {
+				ensure!(linked == who, Error::<T>::AddressAlreadyLinked);
+			}
+
+			SubstrateAccounts::<T>::insert(address, who);
+		}
+
+		/// Bind the caller to an Ethereum address whose key signed the caller's account id,
+		/// as an `eth_sign` of `claim_message_hash`. With `StoredAddressMapping`, the
+		/// account then controls the EVM balance of that address. The claim is refused while
+		/// the address the account controlled before still holds a balance, which would
+		/// otherwise be stranded.
+		#[weight = 10_000]
+		fn claim_address(origin, address: H160, signature: H520) {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				Self::recover_claim_signer(&who, &signature) == Some(address),
+				Error::<T>::InvalidAddressSignature
+			);
+			ensure!(!EvmAddresses::<T>::contains_key(&who), Error::<T>::AddressAlreadyClaimed);
+			if let Some(linked) = SubstrateAccounts::<T>::get(address) {
+				ensure!(linked == who, Error::<T>::AddressAlreadyLinked);
+			}
+			let previous = T::ConvertAccountId::convert_account_id(&who);
+			let previous_balance = pallet_evm::Module::<T>::accounts(previous).balance;
+			ensure!(
+				previous == address || previous_balance.is_zero(),
+				Error::<T>::PreviousAddressNotEmpty
+			);
+
+			if previous != address && SubstrateAccounts::<T>::get(previous).as_ref() == Some(&who) {
+				SubstrateAccounts::<T>::remove(previous);
+			}
+			EvmAddresses::<T>::insert(&who, address);
+			SubstrateAccounts::<T>::insert(address, who);
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			Self::upgrade_transaction_statuses()
 		}
 
 		// The signature could also look like: `fn on_initialize()`.
@@ -181,7 +363,7 @@ decl_module! {
 				ommers_hash: H256::from_slice(
 					Keccak256::digest(&rlp::encode_list(&ommers)[..]).as_slice(),
 				), // TODO: check ommers hash.
-				beneficiary: H160::default(),
+				beneficiary: Self::find_author(),
 				state_root: H256::default(), // TODO: figure out if there's better way to get a sort-of-valid state root.
 				transactions_root: H256::from_slice(
 					Keccak256::digest(&rlp::encode_list(&transactions)[..]).as_slice(),
@@ -189,15 +371,20 @@ decl_module! {
 				receipts_root: H256::from_slice(
 					Keccak256::digest(&rlp::encode_list(&receipts)[..]).as_slice(),
 				), // TODO: check receipts hash.
-				logs_bloom: Bloom::default(), // TODO: gather the logs bloom from receipts.
+				logs_bloom: receipts.iter().fold(Bloom::default(), |mut bloom, receipt| {
+					bloom.accrue_bloom(&receipt.logs_bloom);
+					bloom
+				}),
 				difficulty: U256::zero(),
 				number: U256::from(
 					UniqueSaturatedInto::<u128>::unique_saturated_into(
 						frame_system::Module::<T>::block_number()
 					)
 				),
-				gas_limit: U256::zero(), // TODO: set this using Ethereum's gas limit change algorithm.
-				gas_used: U256::zero(), // TODO: get this from receipts.
+				gas_limit: T::BlockGasLimit::get(),
+				gas_used: receipts.last()
+					.map(|receipt| receipt.used_gas)
+					.unwrap_or_default(),
 				timestamp: UniqueSaturatedInto::<u64>::unique_saturated_into(
 					pallet_timestamp::Module::<T>::get()
 				),
@@ -213,6 +400,7 @@ decl_module! {
 				ommers,
 			};
 
+			let mut transaction_hashes = Vec::with_capacity(transactions.len());
 			for t in &transactions {
 				let transaction_hash = H256::from_slice(
 					Keccak256::digest(&rlp::encode(t)).as_slice()
@@ -223,10 +411,20 @@ decl_module! {
 						(hash, status.transaction_index)
 					);
 				}
+				transaction_hashes.push(transaction_hash);
 			}
 
+			BlockTransactionHashes::insert(hash, transaction_hashes);
+
 			BlocksAndReceipts::insert(hash, (block, receipts));
 			BlockNumbers::<T>::insert(n, hash);
+			let slot = UniqueSaturatedInto::<u32>::unique_saturated_into(n) % BLOCK_HASH_COUNT;
+			<BlockHashes<T>>::insert(slot, (n, hash));
+
+			let retention = T::TransactionStatusRetention::get();
+			if !retention.is_zero() && n > retention {
+				Self::prune_transaction_statuses(n - retention);
+			}
 		}
 
 		// A runtime code run after every block and have access to extended set of APIs.
@@ -244,12 +442,77 @@ impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
 	type Call = Call<T>;
 
 	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		if let Call::transact(transaction) = call {
+			let chain_id = decode_v(transaction.signature.v()).and_then(|(chain_id, _)| chain_id);
+			if chain_id != Some(T::ChainId::get()) {
+				return InvalidTransaction::Custom(
+					TransactionValidationError::InvalidChainId as u8
+				).into();
+			}
+			if transaction.gas_limit < Self::intrinsic_gas(transaction) {
+				return InvalidTransaction::Custom(
+					TransactionValidationError::GasLimitTooLow as u8
+				).into();
+			}
+			if transaction.gas_limit > U256::from(u32::max_value()) {
+				return InvalidTransaction::Custom(
+					TransactionValidationError::GasLimitTooHigh as u8
+				).into();
+			}
+			// Outside of block building nothing is pending, so this only rejects transactions
+			// that could never fit; within a block it defers them to the next one.
+			if !Self::fits_in_block(transaction) {
+				return InvalidTransaction::ExhaustsResources.into();
+			}
+			let source = match recover_signer(transaction) {
+				Some((source, _)) => source,
+				None => return InvalidTransaction::BadProof.into(),
+			};
+			match Self::max_cost(transaction) {
+				Some(max_cost) if max_cost <= pallet_evm::Module::<T>::accounts(source).balance => (),
+				Some(_) => return InvalidTransaction::Payment.into(),
+				None => return InvalidTransaction::Custom(
+					TransactionValidationError::FeeOverflow as u8
+				).into(),
+			}
+			if Self::effective_gas_price(transaction).is_none() {
+				return InvalidTransaction::Custom(
+					TransactionValidationError::GasPriceTooLow as u8
+				).into();
+			}
+
+			// The pool builds blocks in priority order among transactions whose required
+			// tags are provided, so ready transactions are taken highest gas price first
+			// while each sender's nonces stay in sequence.
+			let account_nonce = pallet_evm::Module::<T>::accounts(source).nonce;
+			if transaction.nonce < account_nonce {
+				return InvalidTransaction::Stale.into();
+			}
+			let mut builder = ValidTransaction::with_tag_prefix("Ethereum")
+				.priority(Self::priority(transaction))
+				.and_provides((source, transaction.nonce));
+			if transaction.nonce > account_nonce {
+				builder = builder.and_requires((source, transaction.nonce - 1));
+			}
+			return builder.build();
+		}
+
 		ValidTransaction::with_tag_prefix("Ethereum")
 			.and_provides(call)
 			.build()
 	}
 }
 
+/// Ethereum address of an account: the one it claimed with `claim_address`, or the one
+/// `F` derives for it otherwise.
+pub struct StoredAddressMapping<T, F>(sp_std::marker::PhantomData<(T, F)>);
+
+impl<T: Trait, F: ConvertAccountId<T::AccountId>> ConvertAccountId<T::AccountId> for StoredAddressMapping<T, F> {
+	fn convert_account_id(account: &T::AccountId) -> H160 {
+		EvmAddresses::<T>::get(account).unwrap_or_else(|| F::convert_account_id(account))
+	}
+}
+
 // The main implementation block for the pallet. Functions here fall into three broad
 // categories:
 // - Public interface. These are functions that are `pub` and generally fall into inspector
@@ -318,6 +581,26 @@ impl<T: Trait> Module<T> {
 		None
 	}
 
+	/// Blocks `from..=to` in ascending order. At most `MAX_BLOCKS_IN_RANGE` block numbers
+	/// are scanned; numbers without a block are skipped.
+	pub fn blocks_in_range(from: T::BlockNumber, to: T::BlockNumber) -> Vec<ethereum::Block> {
+		let mut blocks = Vec::new();
+		let mut number = from;
+		for _ in 0..MAX_BLOCKS_IN_RANGE {
+			if number > to {
+				break;
+			}
+			if let Some(block) = Self::block_by_number(number) {
+				blocks.push(block);
+			}
+			if number == to {
+				break;
+			}
+			number += One::one();
+		}
+		blocks
+	}
+
 	pub fn block_by_hash(hash: H256) -> Option<ethereum::Block> {
 		if let Some((block, _receipt)) = BlocksAndReceipts::get(hash) {
 			return Some(block)
@@ -325,66 +608,433 @@ impl<T: Trait> Module<T> {
 		None
 	}
 
-	/// Execute an Ethereum transaction, ignoring transaction signatures.
-	pub fn execute(source: H160, transaction: ethereum::Transaction) {
-		let transaction_hash = H256::from_slice(
-			Keccak256::digest(&rlp::encode(&transaction)).as_slice()
+	/// Number of transactions in the block with the given number, `None` for unknown blocks.
+	pub fn block_transaction_count_by_number(number: T::BlockNumber) -> Option<U256> {
+		Self::block_by_number(number).map(|block| U256::from(block.transactions.len()))
+	}
+
+	/// Number of transactions in the block with the given hash, `None` for unknown blocks.
+	pub fn block_transaction_count_by_hash(hash: H256) -> Option<U256> {
+		Self::block_by_hash(hash).map(|block| U256::from(block.transactions.len()))
+	}
+
+	/// Hash of the given Ethereum block, as seen by the `BLOCKHASH` opcode: only the
+	/// `BLOCK_HASH_COUNT` blocks before the current one are available, others are zero.
+	pub fn block_hash(number: T::BlockNumber) -> H256 {
+		let current = frame_system::Module::<T>::block_number();
+		if number >= current || current - number > BLOCK_HASH_COUNT.into() {
+			return H256::default()
+		}
+
+		let slot = UniqueSaturatedInto::<u32>::unique_saturated_into(number) % BLOCK_HASH_COUNT;
+		match <BlockHashes<T>>::get(slot) {
+			Some((stored, hash)) if stored == number => hash,
+			_ => H256::default(),
+		}
+	}
+
+	/// Author of the current block, or the zero address if it cannot be determined.
+	pub fn find_author() -> H160 {
+		let digest = frame_system::Module::<T>::digest();
+		let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
+		T::FindAuthor::find_author(pre_runtime_digests).unwrap_or_default()
+	}
+
+	/// Hashes of all transactions in the block with the given hash, in block order.
+	pub fn block_transaction_hashes(hash: H256) -> Option<Vec<H256>> {
+		BlockTransactionHashes::get(hash)
+	}
+
+	/// Remove the statuses and locations of all transactions in the given block, along
+	/// with the list of their hashes.
+	fn prune_transaction_statuses(number: T::BlockNumber) {
+		if !<BlockNumbers<T>>::contains_key(number) {
+			return
+		}
+		let hash = <BlockNumbers<T>>::get(number);
+		for transaction_hash in BlockTransactionHashes::take(hash).unwrap_or_default() {
+			TransactionStatuses::remove(transaction_hash);
+			Transactions::remove(transaction_hash);
+		}
+	}
+
+	/// Convert the statuses stored in the `LegacyTransactionStatus` layout, reporting their
+	/// gas usage and log positions as zero.
+	fn upgrade_transaction_statuses() -> Weight {
+		if TransactionStatusLayout::get() >= TRANSACTION_STATUS_LAYOUT {
+			return 0;
+		}
+		<TransactionStatuses as IterableStorageMap<H256, TransactionStatus>>::translate(
+			|_, status: LegacyTransactionStatus| Some(status.into())
 		);
-		let transaction_index = PendingTransactionsAndReceipts::get().len() as u32;
+		TransactionStatusLayout::put(TRANSACTION_STATUS_LAYOUT);
+		T::MaximumBlockWeight::get()
+	}
+
+	/// Number of recent blocks whose transaction statuses are kept, zero for all.
+	pub fn transaction_status_retention() -> T::BlockNumber {
+		T::TransactionStatusRetention::get()
+	}
+
+	/// Minimum gas price accepted by the chain, as set by the EVM fee calculator.
+	pub fn gas_price() -> U256 {
+		T::FeeCalculator::min_gas_price()
+	}
+
+	/// Gas price `transaction` pays at the current base fee, the minimum gas price, or
+	/// `None` if it does not cover it.
+	pub fn effective_gas_price(transaction: &ethereum::Transaction) -> Option<U256> {
+		effective_gas_price(transaction.gas_price, transaction.gas_price, Self::gas_price())
+	}
+
+	/// Pool priority of a transaction: its gas price, saturated to `u64`.
+	pub fn priority(transaction: &ethereum::Transaction) -> u64 {
+		transaction.gas_price.min(U256::from(u64::max_value())).low_u64()
+	}
+
+	/// Most a transaction can charge its sender, `gas_price * gas_limit + value`, or `None`
+	/// if that does not fit in 256 bits.
+	pub fn max_cost(transaction: &ethereum::Transaction) -> Option<U256> {
+		transaction.gas_price
+			.checked_mul(transaction.gas_limit)?
+			.checked_add(transaction.value)
+	}
+
+	/// Gas charged for a transaction before any code is executed: the base cost, the
+	/// creation and init code costs for contract deployments and the cost of each input
+	/// byte.
+	pub fn intrinsic_gas(transaction: &ethereum::Transaction) -> U256 {
+		let zero_bytes = transaction.input.iter().filter(|b| **b == 0).count() as u64;
+		let non_zero_bytes = transaction.input.len() as u64 - zero_bytes;
+
+		let mut gas = U256::from(G_TRANSACTION);
+		if let ethereum::TransactionAction::Create = transaction.action {
+			gas += U256::from(G_TXCREATE);
+			gas += U256::from(Self::init_code_gas(&transaction.input));
+		}
+		gas += U256::from(zero_bytes) * U256::from(G_TXDATAZERO);
+		gas += U256::from(non_zero_bytes) * U256::from(G_TXDATANONZERO);
+		gas
+	}
+
+	/// Gas charged for the words of `init_code`, which the EVM does not meter itself.
+	pub fn init_code_gas(init_code: &[u8]) -> u32 {
+		let words = (init_code.len() as u64 + 31) / 32;
+		words.saturating_mul(G_INITCODEWORD).min(u32::max_value() as u64) as u32
+	}
+
+	/// Length in bytes of the code deployed at `address`, zero for accounts without code.
+	pub fn account_code_len(address: H160) -> u64 {
+		pallet_evm::Module::<T>::account_codes(address).len() as u64
+	}
+
+	/// Keccak hash of the code deployed at `address`, `EMPTY_CODE_HASH` for accounts
+	/// without code. It is derived from the stored code, so accounts seeded at genesis
+	/// report it from block zero.
+	pub fn account_code_hash(address: H160) -> H256 {
+		let code = pallet_evm::Module::<T>::account_codes(address);
+		if code.is_empty() {
+			return H256::from(EMPTY_CODE_HASH);
+		}
+		H256::from_slice(Keccak256::digest(&code).as_slice())
+	}
+
+	/// Value of storage slot `index` of `address`, zero for slots never written.
+	pub fn storage_at(address: H160, index: U256) -> H256 {
+		let mut slot = [0u8; 32];
+		index.to_big_endian(&mut slot);
+		pallet_evm::Module::<T>::account_storages(address, H256::from(slot))
+	}
+
+	/// Execute a call without applying its changes, returning its output and the gas it
+	/// used, or why it did not succeed.
+	pub fn call(
+		from: H160,
+		to: H160,
+		data: Vec<u8>,
+		value: U256,
+		gas_limit: U256,
+		gas_price: U256,
+		nonce: Option<U256>,
+	) -> Result<(Vec<u8>, U256), ExecutionError> {
+		let gas_limit = gas_limit.min(U256::from(u32::max_value())).low_u32();
+		let result = Self::execute_evm(
+			from,
+			value,
+			gas_limit,
+			0,
+			gas_price,
+			nonce,
+			false,
+			|executor| executor.transact_call(from, to, value, data, gas_limit as usize),
+		);
+		Self::execution_result(result)
+	}
+
+	/// Execute a contract creation without applying its changes, returning the code it
+	/// would deploy and the gas it used, or why it did not succeed.
+	pub fn create(
+		from: H160,
+		data: Vec<u8>,
+		value: U256,
+		gas_limit: U256,
+		gas_price: U256,
+		nonce: Option<U256>,
+	) -> Result<(Vec<u8>, U256), ExecutionError> {
+		let gas_limit = gas_limit.min(U256::from(u32::max_value())).low_u32();
+		let init_code_gas = Self::init_code_gas(&data);
+		if gas_limit < init_code_gas {
+			return Err(ExecutionError::OutOfGas);
+		}
+		let result = Self::execute_evm(
+			from,
+			value,
+			gas_limit,
+			init_code_gas,
+			gas_price,
+			nonce,
+			false,
+			|executor| {
+				let address = executor.create_address(CreateScheme::Legacy { caller: from });
+				let reason = executor.transact_create(
+					from,
+					value,
+					data,
+					(gas_limit - init_code_gas) as usize,
+				);
+				(reason, executor.code(address))
+			},
+		);
+		Self::execution_result(result)
+	}
 
-		let status = match transaction.action {
+	/// Outcome of a read-only execution, as reported to RPC callers.
+	fn execution_result(
+		result: Result<(ExitReason, Vec<u8>, U256), Error<T>>,
+	) -> Result<(Vec<u8>, U256), ExecutionError> {
+		let (reason, output, gas_used) = result.map_err(|_| ExecutionError::Failed)?;
+		match reason {
+			ExitReason::Succeed(_) => Ok((output, gas_used)),
+			ExitReason::Revert(_) => Err(ExecutionError::Reverted(output)),
+			ExitReason::Error(ExitError::OutOfGas) => Err(ExecutionError::OutOfGas),
+			ExitReason::Error(_) | ExitReason::Fatal(_) => Err(ExecutionError::Failed),
+		}
+	}
+
+	/// Execute an Ethereum transaction, ignoring transaction signatures. Gas limits above
+	/// `u32::MAX` are saturated.
+	pub fn execute(source: H160, transaction: ethereum::Transaction) -> DispatchResult {
+		let event_count = frame_system::Module::<T>::event_count();
+		let gas_limit = transaction.gas_limit.min(U256::from(u32::max_value())).low_u32();
+		let (to, contract_address, gas_used) = match transaction.action {
 			ethereum::TransactionAction::Call(target) => {
-				pallet_evm::Module::<T>::execute_call(
+				let (_, _, gas_used) = Self::execute_evm(
 					source,
-					target,
-					transaction.input.clone(),
 					transaction.value,
-					transaction.gas_limit.low_u32(),
+					gas_limit,
+					0,
 					transaction.gas_price,
 					Some(transaction.nonce),
 					true,
-				).unwrap(); // TODO: handle error
-
-				TransactionStatus {
-					transaction_hash,
-					transaction_index,
-					from: source,
-					to: Some(target),
-					contract_address: None,
-					logs: Vec::new(), // TODO: feed in logs.
-					logs_bloom: Bloom::default(), // TODO: feed in bloom.
-				}
+					|executor| executor.transact_call(
+						source,
+						target,
+						transaction.value,
+						transaction.input.clone(),
+						gas_limit as usize,
+					),
+				)?;
+
+				(Some(target), None, gas_used)
 			},
 			ethereum::TransactionAction::Create => {
-				let contract_address = pallet_evm::Module::<T>::execute_create(
+				let init_code_gas = Self::init_code_gas(&transaction.input);
+				let (_, contract_address, gas_used) = Self::execute_evm(
 					source,
-					transaction.input.clone(),
 					transaction.value,
-					transaction.gas_limit.low_u32(),
+					gas_limit,
+					init_code_gas,
 					transaction.gas_price,
 					Some(transaction.nonce),
 					true,
-				).unwrap().1; // TODO: handle error
-
-				TransactionStatus {
-					transaction_hash,
-					transaction_index,
-					from: source,
-					to: None,
-					contract_address: Some(contract_address),
-					logs: Vec::new(), // TODO: feed in logs.
-					logs_bloom: Bloom::default(), // TODO: feed in bloom.
-				}
+					|executor| {
+						let address = executor.create_address(CreateScheme::Legacy { caller: source });
+						let reason = executor.transact_create(
+							source,
+							transaction.value,
+							transaction.input.clone(),
+							gas_limit.saturating_sub(init_code_gas) as usize,
+						);
+						(reason, address)
+					},
+				)?;
+
+				(None, Some(contract_address), gas_used)
 			},
 		};
 
+		let logs = Self::logs_since(event_count);
+		Self::store_transaction(source, transaction, to, contract_address, gas_used, logs);
+		Ok(())
+	}
+
+	/// Execute an EVM operation from `source` against the pallet's backend, so `BLOCKHASH`
+	/// sees Ethereum block hashes. `base_gas` out of `gas_limit` is charged up front for
+	/// costs the EVM does not meter. The changes are kept, and the sender pays `gas_price`
+	/// for the gas used, only if `apply_state` is set: read-only executions can be made
+	/// from accounts that could not fund them.
+	fn execute_evm<F, R>(
+		source: H160,
+		value: U256,
+		gas_limit: u32,
+		base_gas: u32,
+		gas_price: U256,
+		nonce: Option<U256>,
+		apply_state: bool,
+		f: F,
+	) -> Result<(ExitReason, R, U256), Error<T>> where
+		F: FnOnce(&mut StackExecutor<backend::Backend<T>>) -> (ExitReason, R),
+	{
+		ensure!(gas_limit >= base_gas, Error::<T>::GasLimitTooLow);
+		let vicinity = Vicinity { gas_price, origin: source };
+		let mut backend = backend::Backend::<T>::new(&vicinity);
+		let mut executor = StackExecutor::new_with_precompile(
+			&backend,
+			(gas_limit - base_gas) as usize,
+			&EVM_CONFIG,
+			T::Precompiles::execute,
+		);
+
+		let total_fee = if apply_state {
+			gas_price.checked_mul(U256::from(gas_limit)).ok_or(Error::<T>::FeeOverflow)?
+		} else {
+			U256::zero()
+		};
+		let total_payment = value.checked_add(total_fee).ok_or(Error::<T>::FeeOverflow)?;
+		let source_account = pallet_evm::Module::<T>::accounts(source);
+		ensure!(source_account.balance >= total_payment, Error::<T>::InsufficientBalance);
+		if let Some(nonce) = nonce {
+			ensure!(source_account.nonce == nonce, Error::<T>::InvalidNonce);
+		}
+		executor.withdraw(source, total_fee).map_err(|_| Error::<T>::InsufficientBalance)?;
+
+		let (reason, output) = f(&mut executor);
+		let used_gas = U256::from(executor.used_gas()).saturating_add(U256::from(base_gas));
+		let actual_fee = executor.fee(gas_price)
+			.saturating_add(gas_price.saturating_mul(U256::from(base_gas)))
+			.min(total_fee);
+		executor.deposit(source, total_fee - actual_fee);
+
+		if apply_state {
+			let (values, logs) = executor.deconstruct();
+			backend.apply(values, logs, true);
+		}
+
+		Ok((reason, output, used_gas))
+	}
+
+	/// Logs deposited by the EVM after the first `event_count` events of the block.
+	fn logs_since(event_count: u32) -> Vec<Log> {
+		frame_system::Module::<T>::events().into_iter()
+			.skip(event_count as usize)
+			.filter_map(|record| match T::EvmEvents::convert(record.event) {
+				Some(pallet_evm::RawEvent::Log(log)) => Some(Log {
+					address: log.address,
+					topics: log.topics,
+					data: log.data,
+				}),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Message an Ethereum key signs to let `account` claim its address: the `eth_sign`
+	/// hash of the Keccak-256 hash of the encoded account id.
+	pub fn claim_message_hash(account: &T::AccountId) -> H256 {
+		let account_hash = Keccak256::digest(&account.encode());
+		let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+		message.extend_from_slice(account_hash.as_slice());
+		H256::from_slice(Keccak256::digest(&message).as_slice())
+	}
+
+	fn recover_claim_signer(account: &T::AccountId, signature: &H520) -> Option<H160> {
+		let message = Self::claim_message_hash(account);
+		let public = sp_io::crypto::secp256k1_ecdsa_recover(
+			signature.as_fixed_bytes(),
+			message.as_fixed_bytes(),
+		).ok()?;
+		Some(H160::from(H256::from_slice(Keccak256::digest(&public).as_slice())))
+	}
+
+	/// Whether the gas limit of `transaction` fits in the current block, after the gas used
+	/// by the transactions already in it.
+	fn fits_in_block(transaction: &ethereum::Transaction) -> bool {
+		let used = PendingTransactionsAndReceipts::get().last()
+			.map(|(_, receipt)| receipt.used_gas)
+			.unwrap_or_default();
+		used.saturating_add(transaction.gas_limit) <= T::BlockGasLimit::get()
+	}
+
+	/// Bloom filter of the addresses and topics of `logs`.
+	pub fn logs_bloom(logs: &[Log]) -> Bloom {
+		let mut bloom = Bloom::default();
+		for log in logs {
+			bloom.accrue(BloomInput::Raw(&log.address[..]));
+			for topic in &log.topics {
+				bloom.accrue(BloomInput::Raw(&topic[..]));
+			}
+		}
+		bloom
+	}
+
+	/// Record an executed transaction as pending in the current block. Its logs are numbered
+	/// after the logs of the transactions before it in the block.
+	fn store_transaction(
+		source: H160,
+		transaction: ethereum::Transaction,
+		to: Option<H160>,
+		contract_address: Option<H160>,
+		gas_used: U256,
+		logs: Vec<Log>,
+	) {
+		let transaction_hash = H256::from_slice(
+			Keccak256::digest(&rlp::encode(&transaction)).as_slice()
+		);
+		let pending = PendingTransactionsAndReceipts::get();
+		let transaction_index = pending.len() as u32;
+		let previous_cumulative_gas_used = pending.last()
+			.map(|(_, receipt)| receipt.used_gas)
+			.unwrap_or_default();
+		let log_index = pending.iter()
+			.map(|(_, receipt)| receipt.logs.len() as u32)
+			.sum();
+		let cumulative_gas_used = previous_cumulative_gas_used.saturating_add(gas_used);
+		let logs_bloom = Self::logs_bloom(&logs);
+
+		let status = TransactionStatus {
+			transaction_hash,
+			transaction_index,
+			from: source,
+			to,
+			contract_address,
+			gas_used,
+			cumulative_gas_used,
+			log_index,
+			logs: logs.clone(),
+			logs_bloom,
+		};
+
+		// Chains started with this layout have nothing to convert on their first upgrade.
+		if TransactionStatusLayout::get() < TRANSACTION_STATUS_LAYOUT {
+			TransactionStatusLayout::put(TRANSACTION_STATUS_LAYOUT);
+		}
 		TransactionStatuses::insert(transaction_hash, status);
 
 		let receipt = ethereum::Receipt {
 			state_root: H256::default(), // TODO: should be okay / error status.
-			used_gas: U256::default(), // TODO: set this.
-			logs_bloom: Bloom::default(), // TODO: set this.
-			logs: Vec::new(), // TODO: set this.
+			used_gas: cumulative_gas_used,
+			logs_bloom,
+			logs,
 		};
 
 		PendingTransactionsAndReceipts::append((transaction, receipt));
@@ -19,20 +19,28 @@
 use super::*;
 use crate::{Module, Trait};
 use ethereum::{TransactionAction, TransactionSignature};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
 use pallet_evm::{FeeCalculator, HashTruncateConvertAccountId};
 use rlp::*;
 use sp_core::{H160, H256, U256};
+use std::collections::BTreeMap;
 use sp_runtime::{
 	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
-	ModuleId, Perbill,
+	traits::{BlakeTwo256, Convert, IdentityLookup},
+	ConsensusEngineId, ModuleId, Perbill,
 };
 
 impl_outer_origin! {
 	pub enum Origin for Test where system = frame_system {}
 }
 
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		frame_system<T>,
+		pallet_evm<T>,
+	}
+}
+
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of pallets we want to use.
@@ -55,7 +63,7 @@ impl frame_system::Trait for Test {
 	type AccountId = H160;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = TestEvent;
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type DbWeight = ();
@@ -111,13 +119,41 @@ impl pallet_evm::Trait for Test {
 	type FeeCalculator = FixedGasPrice;
 	type ConvertAccountId = HashTruncateConvertAccountId<BlakeTwo256>;
 	type Currency = Balances;
-	type Event = ();
+	type Event = TestEvent;
 	type Precompiles = ();
 }
 
+parameter_types! {
+	pub const TransactionStatusRetention: u64 = 3;
+	pub const BlockGasLimit: U256 = U256([10_000_000, 0, 0, 0]);
+}
+
+pub struct EvmEvents;
+impl Convert<TestEvent, Option<pallet_evm::Event<Test>>> for EvmEvents {
+	fn convert(event: TestEvent) -> Option<pallet_evm::Event<Test>> {
+		match event {
+			TestEvent::pallet_evm(event) => Some(event),
+			_ => None,
+		}
+	}
+}
+
+pub struct FixedAuthor;
+impl FindAuthor<H160> for FixedAuthor {
+	fn find_author<'a, I>(_digests: I) -> Option<H160> where
+		I: 'a + IntoIterator<Item=(ConsensusEngineId, &'a [u8])>
+	{
+		Some(H160::repeat_byte(0xab))
+	}
+}
+
 impl Trait for Test {
     type Event = ();
     type ChainId = ChainId;
+	type TransactionStatusRetention = TransactionStatusRetention;
+	type FindAuthor = FixedAuthor;
+	type BlockGasLimit = BlockGasLimit;
+	type EvmEvents = EvmEvents;
 }
 
 pub type System = frame_system::Module<Test>;
@@ -156,6 +192,19 @@ pub fn new_test_ext(accounts_len: usize) -> (Vec<AccountInfo>, sp_io::TestExtern
 	(pairs, ext)
 }
 
+/// Externalities whose genesis holds the given EVM accounts.
+pub fn genesis_ext(
+	accounts: BTreeMap<H160, pallet_evm::GenesisAccount>,
+) -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_evm::GenesisConfig { accounts }
+		.assimilate_storage::<Test>(&mut storage)
+		.unwrap();
+	storage.into()
+}
+
 pub fn contract_address(sender: H160, nonce: u64) -> H160 {
 	let mut rlp = RlpStream::new_list(2);
 	rlp.append(&sender);
@@ -180,27 +229,42 @@ pub struct UnsignedTransaction {
 }
 
 impl UnsignedTransaction {
-	fn signing_rlp_append(&self, s: &mut RlpStream) {
-		s.begin_list(9);
+	fn signing_rlp_append(&self, s: &mut RlpStream, chain_id: Option<u64>) {
+		s.begin_list(if chain_id.is_some() { 9 } else { 6 });
 		s.append(&self.nonce);
 		s.append(&self.gas_price);
 		s.append(&self.gas_limit);
 		s.append(&self.action);
 		s.append(&self.value);
 		s.append(&self.input);
-		s.append(&42u8); // TODO: move this chain id into the frame ethereum configuration
-		s.append(&0u8);
-		s.append(&0u8);
+		if let Some(chain_id) = chain_id {
+			s.append(&chain_id);
+			s.append(&0u8);
+			s.append(&0u8);
+		}
 	}
 
-	fn signing_hash(&self) -> H256 {
+	fn signing_hash(&self, chain_id: Option<u64>) -> H256 {
 		let mut stream = RlpStream::new();
-		self.signing_rlp_append(&mut stream);
+		self.signing_rlp_append(&mut stream, chain_id);
 		H256::from_slice(&Keccak256::digest(&stream.drain()).as_slice())
 	}
 
 	pub fn sign(self, key: &H256) -> Transaction {
-		let hash = self.signing_hash();
+		self.sign_with_chain_id(key, ChainId::get())
+	}
+
+	pub fn sign_with_chain_id(self, key: &H256, chain_id: u64) -> Transaction {
+		self.sign_with(key, Some(chain_id))
+	}
+
+	/// Sign without replay protection, as before EIP-155.
+	pub fn sign_without_chain_id(self, key: &H256) -> Transaction {
+		self.sign_with(key, None)
+	}
+
+	fn sign_with(self, key: &H256, chain_id: Option<u64>) -> Transaction {
+		let hash = self.signing_hash(chain_id);
 		let msg = {
 			let mut a = [0u8; 32];
 			for i in 0..32 {
@@ -211,8 +275,12 @@ impl UnsignedTransaction {
 		let s = secp256k1::sign(&msg, &secp256k1::SecretKey::parse_slice(&key[..]).unwrap());
 		let sig = s.0.serialize();
 
+		let v = match chain_id {
+			Some(chain_id) => chain_id * 2 + 35,
+			None => 27,
+		};
 		let sig = TransactionSignature::new(
-			0x78,
+			v + s.1.serialize() as u64,
 			H256::from_slice(&sig[0..32]),
 			H256::from_slice(&sig[32..64]),
 		)
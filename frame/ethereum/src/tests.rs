@@ -21,8 +21,16 @@ use super::*;
 use mock::*;
 use rustc_hex::FromHex;
 use std::str::FromStr;
+use std::collections::BTreeMap;
 use ethereum::TransactionSignature;
-use frame_support::assert_noop;
+use frame_support::{
+	assert_noop, assert_ok, StorageMap,
+	traits::{OnFinalize, OnRuntimeUpgrade},
+	unsigned::ValidateUnsigned,
+};
+use sp_runtime::traits::BlakeTwo256;
+use sp_runtime::transaction_validity::{TransactionSource, InvalidTransaction};
+use pallet_evm::HashTruncateConvertAccountId;
 
 // This ERC-20 contract mints the maximum amount of tokens to the contract creator.
 // pragma solidity ^0.5.0;
@@ -33,9 +41,13 @@ use frame_support::assert_noop;
 const ERC20_CONTRACT_BYTECODE: &str = "608060405234801561001057600080fd5b50610041337fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff61004660201b60201c565b610291565b600073ffffffffffffffffffffffffffffffffffffffff168273ffffffffffffffffffffffffffffffffffffffff1614156100e9576040517f08c379a000000000000000000000000000000000000000000000000000000000815260040180806020018281038252601f8152602001807f45524332303a206d696e7420746f20746865207a65726f20616464726573730081525060200191505060405180910390fd5b6101028160025461020960201b610c7c1790919060201c565b60028190555061015d816000808573ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff1681526020019081526020016000205461020960201b610c7c1790919060201c565b6000808473ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020819055508173ffffffffffffffffffffffffffffffffffffffff16600073ffffffffffffffffffffffffffffffffffffffff167fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef836040518082815260200191505060405180910390a35050565b600080828401905083811015610287576040517f08c379a000000000000000000000000000000000000000000000000000000000815260040180806020018281038252601b8152602001807f536166654d6174683a206164646974696f6e206f766572666c6f77000000000081525060200191505060405180910390fd5b8091505092915050565b610e3a806102a06000396000f3fe608060405234801561001057600080fd5b50600436106100885760003560e01c806370a082311161005b57806370a08231146101fd578063a457c2d714610255578063a9059cbb146102bb578063dd62ed3e1461032157610088565b8063095ea7b31461008d57806318160ddd146100f357806323b872dd146101115780633950935114610197575b600080fd5b6100d9600480360360408110156100a357600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff16906020019092919080359060200190929190505050610399565b604051808215151515815260200191505060405180910390f35b6100fb6103b7565b6040518082815260200191505060405180910390f35b61017d6004803603606081101561012757600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff169060200190929190803573ffffffffffffffffffffffffffffffffffffffff169060200190929190803590602001909291905050506103c1565b604051808215151515815260200191505060405180910390f35b6101e3600480360360408110156101ad57600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff1690602001909291908035906020019092919050505061049a565b604051808215151515815260200191505060405180910390f35b61023f6004803603602081101561021357600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff16906020019092919050505061054d565b6040518082815260200191505060405180910390f35b6102a16004803603604081101561026b57600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff16906020019092919080359060200190929190505050610595565b604051808215151515815260200191505060405180910390f35b610307600480360360408110156102d157600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff16906020019092919080359060200190929190505050610662565b604051808215151515815260200191505060405180910390f35b6103836004803603604081101561033757600080fd5b81019080803573ffffffffffffffffffffffffffffffffffffffff169060200190929190803573ffffffffffffffffffffffffffffffffffffffff169060200190929190505050610680565b6040518082815260200191505060405180910390f35b60006103ad6103a6610707565b848461070f565b6001905092915050565b6000600254905090565b60006103ce848484610906565b61048f846103da610707565b61048a85604051806060016040528060288152602001610d7060289139600160008b73ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff1681526020019081526020016000206000610440610707565b73ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002054610bbc9092919063ffffffff16565b61070f565b600190509392505050565b60006105436104a7610707565b8461053e85600160006104b8610707565b73ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060008973ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002054610c7c90919063ffffffff16565b61070f565b6001905092915050565b60008060008373ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020549050919050565b60006106586105a2610707565b8461065385604051806060016040528060258152602001610de160259139600160006105cc610707565b73ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060008a73ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002054610bbc9092919063ffffffff16565b61070f565b6001905092915050565b600061067661066f610707565b8484610906565b6001905092915050565b6000600160008473ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060008373ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002054905092915050565b600033905090565b600073ffffffffffffffffffffffffffffffffffffffff168373ffffffffffffffffffffffffffffffffffffffff161415610795576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182810382526024815260200180610dbd6024913960400191505060405180910390fd5b600073ffffffffffffffffffffffffffffffffffffffff168273ffffffffffffffffffffffffffffffffffffffff16141561081b576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182810382526022815260200180610d286022913960400191505060405180910390fd5b80600160008573ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002060008473ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020819055508173ffffffffffffffffffffffffffffffffffffffff168373ffffffffffffffffffffffffffffffffffffffff167f8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925836040518082815260200191505060405180910390a3505050565b600073ffffffffffffffffffffffffffffffffffffffff168373ffffffffffffffffffffffffffffffffffffffff16141561098c576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182810382526025815260200180610d986025913960400191505060405180910390fd5b600073ffffffffffffffffffffffffffffffffffffffff168273ffffffffffffffffffffffffffffffffffffffff161415610a12576040517f08c379a0000000000000000000000000000000000000000000000000000000008152600401808060200182810382526023815260200180610d056023913960400191505060405180910390fd5b610a7d81604051806060016040528060268152602001610d4a602691396000808773ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002054610bbc9092919063ffffffff16565b6000808573ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002081905550610b10816000808573ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff16815260200190815260200160002054610c7c90919063ffffffff16565b6000808473ffffffffffffffffffffffffffffffffffffffff1673ffffffffffffffffffffffffffffffffffffffff168152602001908152602001600020819055508173ffffffffffffffffffffffffffffffffffffffff168373ffffffffffffffffffffffffffffffffffffffff167fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef836040518082815260200191505060405180910390a3505050565b6000838311158290610c69576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004018080602001828103825283818151815260200191508051906020019080838360005b83811015610c2e578082015181840152602081019050610c13565b50505050905090810190601f168015610c5b5780820380516001836020036101000a031916815260200191505b509250505060405180910390fd5b5060008385039050809150509392505050565b600080828401905083811015610cfa576040517f08c379a000000000000000000000000000000000000000000000000000000000815260040180806020018281038252601b8152602001807f536166654d6174683a206164646974696f6e206f766572666c6f77000000000081525060200191505060405180910390fd5b809150509291505056fe45524332303a207472616e7366657220746f20746865207a65726f206164647265737345524332303a20617070726f766520746f20746865207a65726f206164647265737345524332303a207472616e7366657220616d6f756e7420657863656564732062616c616e636545524332303a207472616e7366657220616d6f756e74206578636565647320616c6c6f77616e636545524332303a207472616e736665722066726f6d20746865207a65726f206164647265737345524332303a20617070726f76652066726f6d20746865207a65726f206164647265737345524332303a2064656372656173656420616c6c6f77616e63652062656c6f77207a65726fa265627a7a72315820c7a5ffabf642bda14700b2de42f8c57b36621af020441df825de45fd2b3e1c5c64736f6c63430005100032";
 
 fn default_erc20_creation_transaction(account: &AccountInfo) -> Transaction {
+	erc20_creation_transaction(account, U256::zero())
+}
+
+fn erc20_creation_transaction(account: &AccountInfo, gas_price: U256) -> Transaction {
 	UnsignedTransaction {
 		nonce: U256::zero(),
-		gas_price: U256::from(0),
+		gas_price,
 		gas_limit: U256::from(0x100000),
 		action: ethereum::TransactionAction::Create,
 		value: U256::zero(),
@@ -43,16 +55,38 @@ fn default_erc20_creation_transaction(account: &AccountInfo) -> Transaction {
 	}.sign(&account.private_key)
 }
 
+fn transfer_transaction(account: &AccountInfo, gas_limit: u64) -> Transaction {
+	UnsignedTransaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0),
+		gas_limit: U256::from(gas_limit),
+		action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+		value: U256::zero(),
+		input: Vec::new(),
+	}.sign(&account.private_key)
+}
+
+fn chain_transfer_transaction(account: &AccountInfo, chain_id: u64) -> Transaction {
+	UnsignedTransaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0),
+		gas_limit: U256::from(G_TRANSACTION),
+		action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+		value: U256::zero(),
+		input: Vec::new(),
+	}.sign_with_chain_id(&account.private_key, chain_id)
+}
+
 #[test]
 fn transaction_should_increment_nonce() {
 	let (pairs, mut ext) = new_test_ext(1);
 	let alice = &pairs[0];
 
 	ext.execute_with(|| {
-		Ethereum::execute(
+		assert_ok!(Ethereum::execute(
 			alice.address,
 			default_erc20_creation_transaction(alice),
-		);
+		));
 		assert_eq!(Evm::accounts(alice.address).nonce, U256::from(1));
 	});
 }
@@ -65,10 +99,10 @@ fn transaction_should_be_added_to_pending() {
 
 	ext.execute_with(|| {
 		let transaction = default_erc20_creation_transaction(alice);
-		Ethereum::execute(
+		assert_ok!(Ethereum::execute(
 			alice.address,
 			transaction.clone(),
-		);
+		));
 		assert_eq!(PendingTransactionsAndReceipts::get().len(), 1);
 		assert_eq!(PendingTransactionsAndReceipts::get()[0].0.input, transaction.input);
 	});
@@ -76,7 +110,6 @@ fn transaction_should_be_added_to_pending() {
 
 
 #[test]
-#[should_panic(expected = "called `Result::unwrap()` on an `Err` value: BalanceLow")]
 fn transaction_without_enough_gas_should_not_work() {
 	let (pairs, mut ext) = new_test_ext(1);
 	let alice = &pairs[0];
@@ -85,9 +118,9 @@ fn transaction_without_enough_gas_should_not_work() {
 		let mut transaction = default_erc20_creation_transaction(alice);
 		transaction.gas_price = U256::from(1);
 
-		Ethereum::execute(
-			alice.address,
-			transaction,
+		assert_noop!(
+			Ethereum::execute(alice.address, transaction),
+			Error::<Test>::InsufficientBalance
 		);
 	});
 }
@@ -100,10 +133,10 @@ fn contract_constructor_should_get_executed() {
 	let alice_storage_address = storage_address(alice.address, H256::zero());
 
 	ext.execute_with(|| {
-		Ethereum::execute(
+		assert_ok!(Ethereum::execute(
 			alice.address,
 			default_erc20_creation_transaction(alice),
-		);
+		));
 		assert_eq!(Evm::account_storages(
 			erc20_address, alice_storage_address
 		), H256::from_str("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap())
@@ -113,8 +146,9 @@ fn contract_constructor_should_get_executed() {
 
 #[test]
 fn source_should_be_derived_from_signature() {
-	let (pairs, mut ext) = new_test_ext(1);
+	let (pairs, _) = new_test_ext(1);
 	let alice = &pairs[0];
+	let mut ext = funded_ext(&[alice.address]);
 
 	let erc20_address = contract_address(alice.address, 0);
 	let alice_storage_address = storage_address(alice.address, H256::zero());
@@ -122,7 +156,7 @@ fn source_should_be_derived_from_signature() {
 	ext.execute_with(|| {
 		Ethereum::transact(
 			Origin::none(),
-			default_erc20_creation_transaction(alice),
+			erc20_creation_transaction(alice, U256::one()),
 		).expect("Failed to execute transaction");
 
 		// We verify the transaction happened with alice account.
@@ -157,10 +191,1454 @@ fn contract_should_be_created_at_given_address() {
 	let erc20_address = contract_address(alice.address, 0);
 
 	ext.execute_with(|| {
-		Ethereum::execute(
+		assert_ok!(Ethereum::execute(
 			alice.address,
 			default_erc20_creation_transaction(alice),
-		);
+		));
 		assert_ne!(Evm::account_codes(erc20_address).len(), 0);
 	});
 }
+
+#[test]
+fn deployed_code_should_match_returned_code() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	// Runtime code `PUSH1 0xff PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`, preceded by init
+	// code copying it to memory and returning it.
+	let runtime_code: Vec<u8> = FromHex::from_hex("60ff60005260206000f3").unwrap();
+	let init_code: Vec<u8> = FromHex::from_hex("600a600c600039600a6000f3").unwrap();
+
+	ext.execute_with(|| {
+		let transaction = UnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::from(0),
+			gas_limit: U256::from(0x100000),
+			action: ethereum::TransactionAction::Create,
+			value: U256::zero(),
+			input: [init_code, runtime_code.clone()].concat(),
+		}.sign(&alice.private_key);
+		assert_ok!(Ethereum::execute(alice.address, transaction));
+
+		assert_eq!(Evm::account_codes(contract_address(alice.address, 0)), runtime_code);
+		assert_eq!(Evm::account_codes(alice.address), Vec::<u8>::new());
+	});
+}
+
+#[test]
+fn storage_written_by_contract_should_be_readable() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		// Constructor `PUSH1 0x2a PUSH1 1 SSTORE STOP`.
+		let transaction = UnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::from(0),
+			gas_limit: U256::from(0x100000),
+			action: ethereum::TransactionAction::Create,
+			value: U256::zero(),
+			input: FromHex::from_hex("602a60015500").unwrap(),
+		}.sign(&alice.private_key);
+		assert_ok!(Ethereum::execute(alice.address, transaction));
+
+		let contract = contract_address(alice.address, 0);
+		assert_eq!(Ethereum::storage_at(contract, U256::from(1)), H256::from_low_u64_be(0x2a));
+		assert_eq!(Ethereum::storage_at(contract, U256::from(2)), H256::default());
+	});
+}
+
+#[test]
+fn contract_code_len_should_match_deployed_code() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	let erc20_address = contract_address(alice.address, 0);
+
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::execute(
+			alice.address,
+			default_erc20_creation_transaction(alice),
+		));
+		assert_eq!(
+			Ethereum::account_code_len(erc20_address),
+			Evm::account_codes(erc20_address).len() as u64
+		);
+		assert_eq!(Ethereum::account_code_len(alice.address), 0);
+	});
+}
+
+#[test]
+fn cumulative_gas_used_should_sum_previous_transactions() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		for nonce in 0..3u64 {
+			let mut transaction = default_erc20_creation_transaction(alice);
+			transaction.nonce = U256::from(nonce);
+			assert_ok!(Ethereum::execute(alice.address, transaction));
+		}
+
+		let mut expected = U256::zero();
+		for (transaction, receipt) in PendingTransactionsAndReceipts::get() {
+			let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+			let status = Ethereum::transaction_status(hash).unwrap();
+
+			assert!(status.gas_used > U256::zero());
+			expected += status.gas_used;
+			assert_eq!(status.cumulative_gas_used, expected);
+			assert_eq!(receipt.used_gas, expected);
+		}
+	});
+}
+
+#[test]
+fn log_indices_should_be_contiguous_across_transactions() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	let log = |byte| Log {
+		address: H160::repeat_byte(byte),
+		topics: vec![H256::repeat_byte(byte)],
+		data: vec![byte],
+	};
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		for (nonce, logs) in vec![vec![log(0), log(1)], Vec::new(), vec![log(2), log(3), log(4)]]
+			.into_iter()
+			.enumerate()
+		{
+			let mut transaction = transfer_transaction(alice, 21_000);
+			transaction.nonce = U256::from(nonce);
+			Ethereum::store_transaction(alice.address, transaction, None, None, U256::zero(), logs);
+		}
+		Ethereum::on_finalize(1);
+
+		let block = Ethereum::block_by_number(1).unwrap();
+		let (_, receipts) = BlocksAndReceipts::get(BlockNumbers::<Test>::get(1)).unwrap();
+		let mut indices = Vec::new();
+		for (transaction, receipt) in block.transactions.iter().zip(receipts) {
+			let hash = H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice());
+			let status = Ethereum::transaction_status(hash).unwrap();
+
+			assert_eq!(status.logs, receipt.logs);
+			assert_eq!(status.logs_bloom, receipt.logs_bloom);
+			assert_eq!(status.logs_bloom, Ethereum::logs_bloom(&status.logs));
+			assert_eq!(status.log_index as usize, indices.len());
+			for (transaction_log_index, log) in status.logs.iter().enumerate() {
+				let log_index = status.log_index as usize + transaction_log_index;
+				assert_eq!(log.data, vec![log_index as u8]);
+				indices.push((log_index, transaction_log_index));
+			}
+		}
+
+		assert_eq!(indices, vec![(0, 0), (1, 1), (2, 0), (3, 1), (4, 2)]);
+	});
+}
+
+fn priced_transfer_transaction(account: &AccountInfo, gas_price: U256) -> Transaction {
+	UnsignedTransaction {
+		nonce: U256::zero(),
+		gas_price,
+		gas_limit: U256::from(G_TRANSACTION),
+		action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+		value: U256::zero(),
+		input: Vec::new(),
+	}.sign(&account.private_key)
+}
+
+#[test]
+fn overflowing_fee_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = priced_transfer_transaction(alice, U256::max_value() / 2);
+		assert_eq!(Ethereum::max_cost(&transaction), None);
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Custom(TransactionValidationError::FeeOverflow as u8).into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::FeeOverflow
+		);
+	});
+}
+
+#[test]
+fn fee_above_balance_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = priced_transfer_transaction(alice, U256::one());
+		assert_eq!(Ethereum::max_cost(&transaction), Some(U256::from(G_TRANSACTION)));
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Payment.into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn transaction_reusing_a_nonce_in_the_same_block_should_be_rejected() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let mut ext = funded_ext(&[alice.address]);
+
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::transact(Origin::none(), priced_transfer_transaction(alice, U256::one())));
+		assert_noop!(
+			Ethereum::transact(Origin::none(), priced_transfer_transaction(alice, U256::from(2))),
+			Error::<Test>::InvalidNonce
+		);
+	});
+}
+
+#[test]
+fn gas_limit_above_32_bits_should_be_rejected() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let mut ext = funded_ext(&[alice.address]);
+
+	ext.execute_with(|| {
+		let transaction = UnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas_limit: U256::from(u32::max_value()) + 1,
+			action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+			value: U256::zero(),
+			input: Vec::new(),
+		}.sign(&alice.private_key);
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Custom(TransactionValidationError::GasLimitTooHigh as u8).into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::GasLimitTooHigh
+		);
+	});
+}
+
+/// Externalities in which each of `addresses` holds a balance in its EVM account.
+fn funded_ext(addresses: &[H160]) -> sp_io::TestExternalities {
+	let accounts = addresses.iter()
+		.map(|address| (*address, pallet_evm::GenesisAccount {
+			nonce: U256::zero(),
+			balance: U256::from(1_000_000_000u64),
+			storage: BTreeMap::new(),
+			code: Vec::new(),
+		}))
+		.collect();
+	genesis_ext(accounts)
+}
+
+fn nonce_tag(address: H160, nonce: u64) -> Vec<u8> {
+	codec::Encode::encode(&("Ethereum", (address, U256::from(nonce))))
+}
+
+#[test]
+fn pool_validity_should_order_by_gas_price_within_nonce_sequence() {
+	let (pairs, _) = new_test_ext(2);
+	let alice = &pairs[0];
+	let bob = &pairs[1];
+	let mut ext = funded_ext(&[alice.address, bob.address]);
+	let transfer = |account: &AccountInfo, nonce: u64, gas_price: u64| UnsignedTransaction {
+		nonce: U256::from(nonce),
+		gas_price: U256::from(gas_price),
+		gas_limit: U256::from(G_TRANSACTION),
+		action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+		value: U256::zero(),
+		input: Vec::new(),
+	}.sign(&account.private_key);
+	let validity = |transaction| Ethereum::validate_unsigned(
+		TransactionSource::External,
+		&Call::transact(transaction),
+	).unwrap();
+
+	ext.execute_with(|| {
+		// Alice's cheap first transaction gates her expensive second one, so Bob's
+		// transaction is preferred over the first but not over the second.
+		let alice_first = validity(transfer(alice, 0, 1));
+		let alice_second = validity(transfer(alice, 1, 9));
+		let bob_first = validity(transfer(bob, 0, 5));
+
+		assert_eq!(alice_first.priority, 1);
+		assert_eq!(alice_first.requires, Vec::<Vec<u8>>::new());
+		assert_eq!(alice_first.provides, vec![nonce_tag(alice.address, 0)]);
+
+		assert_eq!(alice_second.priority, 9);
+		assert_eq!(alice_second.requires, vec![nonce_tag(alice.address, 0)]);
+		assert_eq!(alice_second.provides, vec![nonce_tag(alice.address, 1)]);
+
+		assert_eq!(bob_first.priority, 5);
+		assert_eq!(bob_first.requires, Vec::<Vec<u8>>::new());
+		assert!(bob_first.priority > alice_first.priority);
+		assert!(alice_second.priority > bob_first.priority);
+
+		assert_ok!(Ethereum::execute(alice.address, transfer(alice, 0, 1)));
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transfer(alice, 0, 1)),
+			),
+			InvalidTransaction::Stale.into()
+		);
+	});
+}
+
+#[test]
+fn transfer_with_intrinsic_gas_limit_should_work() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let mut ext = funded_ext(&[alice.address]);
+
+	ext.execute_with(|| {
+		let transaction = priced_transfer_transaction(alice, U256::one());
+		assert_eq!(Ethereum::intrinsic_gas(&transaction), U256::from(G_TRANSACTION));
+		assert_ok!(Ethereum::transact(Origin::none(), transaction));
+	});
+}
+
+#[test]
+fn transfer_below_intrinsic_gas_limit_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = transfer_transaction(alice, G_TRANSACTION - 1);
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Custom(TransactionValidationError::GasLimitTooLow as u8).into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::GasLimitTooLow
+		);
+	});
+}
+
+#[test]
+fn creation_below_intrinsic_gas_limit_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let mut transaction = default_erc20_creation_transaction(alice);
+		let intrinsic_gas = Ethereum::intrinsic_gas(&transaction);
+		assert!(intrinsic_gas > U256::from(G_TRANSACTION + G_TXCREATE));
+
+		transaction.gas_limit = intrinsic_gas - 1;
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::GasLimitTooLow
+		);
+	});
+}
+
+#[test]
+fn creation_intrinsic_gas_should_charge_init_code_words() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let transaction = UnsignedTransaction {
+		nonce: U256::zero(),
+		gas_price: U256::zero(),
+		gas_limit: U256::from(0x100000),
+		action: ethereum::TransactionAction::Create,
+		value: U256::zero(),
+		input: vec![0x60; 33],
+	}.sign(&alice.private_key);
+
+	assert_eq!(Ethereum::init_code_gas(&[]), 0);
+	assert_eq!(Ethereum::init_code_gas(&[0x60; 32]), 2);
+	assert_eq!(Ethereum::init_code_gas(&[0x60; 33]), 4);
+	assert_eq!(
+		Ethereum::intrinsic_gas(&transaction),
+		U256::from(G_TRANSACTION + G_TXCREATE + 33 * G_TXDATANONZERO + 2 * G_INITCODEWORD)
+	);
+}
+
+#[test]
+fn block_transaction_hashes_should_match_transactions() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		for nonce in 0..2u64 {
+			let mut transaction = default_erc20_creation_transaction(alice);
+			transaction.nonce = U256::from(nonce);
+			assert_ok!(Ethereum::execute(alice.address, transaction));
+		}
+		Ethereum::on_finalize(1);
+
+		let hash = BlockNumbers::<Test>::get(1);
+		let block = Ethereum::block_by_hash(hash).unwrap();
+		let expected = block.transactions.iter()
+			.map(|t| H256::from_slice(Keccak256::digest(&rlp::encode(t)).as_slice()))
+			.collect::<Vec<_>>();
+
+		assert_eq!(expected.len(), 2);
+		assert_eq!(Ethereum::block_transaction_hashes(hash), Some(expected));
+		assert_eq!(Ethereum::block_transaction_hashes(H256::repeat_byte(0x42)), None);
+	});
+}
+
+#[test]
+fn transactions_should_be_found_by_block_hash_and_index() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let mut transactions = Vec::new();
+		for nonce in 0..2u64 {
+			let mut transaction = default_erc20_creation_transaction(alice);
+			transaction.nonce = U256::from(nonce);
+			transactions.push(transaction.clone());
+			assert_ok!(Ethereum::execute(alice.address, transaction));
+		}
+		Ethereum::on_finalize(1);
+		let hash = BlockNumbers::<Test>::get(1);
+
+		for (index, expected) in transactions.into_iter().enumerate() {
+			let (transaction, block, status) =
+				Ethereum::transaction_by_block_hash_and_index(hash, index as u32).unwrap();
+			assert_eq!(transaction, expected);
+			assert_eq!(block.transactions.len(), 2);
+			assert_eq!(status.transaction_index, index as u32);
+		}
+		assert!(Ethereum::transaction_by_block_hash_and_index(hash, 2).is_none());
+		assert!(Ethereum::transaction_by_block_hash_and_index(H256::repeat_byte(0x42), 0).is_none());
+	});
+}
+
+#[test]
+fn block_transaction_count_should_match_block() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		for nonce in 0..3u64 {
+			let mut transaction = default_erc20_creation_transaction(alice);
+			transaction.nonce = U256::from(nonce);
+			assert_ok!(Ethereum::execute(alice.address, transaction));
+		}
+		Ethereum::on_finalize(1);
+
+		assert_eq!(Ethereum::block_transaction_count_by_number(1), Some(U256::from(3)));
+		assert_eq!(Ethereum::block_transaction_count_by_number(2), None);
+	});
+}
+
+#[test]
+fn block_transaction_count_by_hash_should_match_block() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		for nonce in 0..3u64 {
+			let mut transaction = default_erc20_creation_transaction(alice);
+			transaction.nonce = U256::from(nonce);
+			assert_ok!(Ethereum::execute(alice.address, transaction));
+		}
+		Ethereum::on_finalize(1);
+		let hash = BlockNumbers::<Test>::get(1);
+
+		assert_eq!(Ethereum::block_transaction_count_by_hash(hash), Some(U256::from(3)));
+		assert_eq!(Ethereum::block_transaction_count_by_hash(H256::repeat_byte(0x42)), None);
+	});
+}
+
+#[test]
+fn transaction_statuses_should_be_pruned_after_retention() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let old = default_erc20_creation_transaction(alice);
+		let old_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&old)).as_slice());
+		assert_ok!(Ethereum::execute(alice.address, old));
+		Ethereum::on_finalize(1);
+
+		let mut recent = default_erc20_creation_transaction(alice);
+		recent.nonce = U256::from(1);
+		let recent_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&recent)).as_slice());
+		assert_ok!(Ethereum::execute(alice.address, recent));
+		Ethereum::on_finalize(2);
+
+		assert!(Ethereum::transaction_status(old_hash).is_some());
+		Ethereum::on_finalize(3);
+		Ethereum::on_finalize(4);
+
+		assert!(Ethereum::transaction_status(old_hash).is_none());
+		assert!(Ethereum::transaction_by_hash(old_hash).is_none());
+		assert_eq!(Ethereum::block_transaction_hashes(BlockNumbers::<Test>::get(1)), None);
+		assert!(Ethereum::transaction_status(recent_hash).is_some());
+		assert_eq!(
+			Ethereum::block_transaction_hashes(BlockNumbers::<Test>::get(2)),
+			Some(vec![recent_hash])
+		);
+	});
+}
+
+#[test]
+fn legacy_transaction_statuses_should_be_upgraded_once() {
+	let (_pairs, mut ext) = new_test_ext(1);
+	let legacy = LegacyTransactionStatus {
+		transaction_hash: H256::repeat_byte(0x01),
+		transaction_index: 2,
+		from: H160::repeat_byte(0xaa),
+		to: Some(H160::repeat_byte(0xbb)),
+		contract_address: None,
+		logs: Vec::new(),
+		logs_bloom: Default::default(),
+	};
+
+	ext.execute_with(|| {
+		let key = <TransactionStatuses as StorageMap<H256, TransactionStatus>>::hashed_key_for(
+			legacy.transaction_hash
+		);
+		frame_support::storage::unhashed::put(&key, &legacy);
+
+		Ethereum::on_runtime_upgrade();
+		let upgraded = Ethereum::transaction_status(legacy.transaction_hash).unwrap();
+		assert_eq!(upgraded, TransactionStatus::from(legacy.clone()));
+		assert_eq!(TransactionStatusLayout::get(), TRANSACTION_STATUS_LAYOUT);
+
+		assert_eq!(Ethereum::on_runtime_upgrade(), 0);
+		assert_eq!(Ethereum::transaction_status(legacy.transaction_hash), Some(upgraded));
+	});
+}
+
+#[test]
+fn statuses_stored_in_the_current_layout_should_not_be_upgraded() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = default_erc20_creation_transaction(alice);
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+		assert_ok!(Ethereum::execute(alice.address, transaction));
+		let status = Ethereum::transaction_status(hash);
+
+		assert_eq!(Ethereum::on_runtime_upgrade(), 0);
+		assert_eq!(Ethereum::transaction_status(hash), status);
+	});
+}
+
+#[test]
+fn block_beneficiary_should_be_the_block_author() {
+	let (_pairs, mut ext) = new_test_ext(1);
+
+	ext.execute_with(|| {
+		Ethereum::on_finalize(1);
+
+		let block = Ethereum::block_by_number(1).unwrap();
+		assert_eq!(Ethereum::find_author(), H160::repeat_byte(0xab));
+		assert_eq!(block.header.beneficiary, H160::repeat_byte(0xab));
+	});
+}
+
+#[test]
+fn block_hash_should_only_be_available_for_recent_blocks() {
+	let (_pairs, mut ext) = new_test_ext(1);
+
+	ext.execute_with(|| {
+		for n in 1..=3 {
+			System::set_block_number(n);
+			Ethereum::on_finalize(n);
+		}
+		System::set_block_number(4);
+
+		assert_eq!(Ethereum::block_hash(1), BlockNumbers::<Test>::get(1));
+		assert_eq!(Ethereum::block_hash(3), BlockNumbers::<Test>::get(3));
+		assert_eq!(Ethereum::block_hash(4), H256::default());
+
+		let n = 1 + BLOCK_HASH_COUNT as u64;
+		System::set_block_number(n);
+		Ethereum::on_finalize(n);
+		System::set_block_number(n + 1);
+
+		assert_eq!(Ethereum::block_hash(1), H256::default());
+		assert_eq!(Ethereum::block_hash(3), BlockNumbers::<Test>::get(3));
+		assert_eq!(Ethereum::block_hash(n), BlockNumbers::<Test>::get(n));
+	});
+}
+
+#[test]
+fn blockhash_opcode_should_return_ethereum_block_hashes() {
+	let caller = H160::repeat_byte(0x01);
+	let contract = H160::repeat_byte(0x42);
+	// `PUSH1 1 BLOCKHASH PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`: returns the hash of block 1.
+	let mut ext = contract_ext(caller, contract, "60014060005260206000f3");
+
+	ext.execute_with(|| {
+		for n in 1..=2 {
+			System::set_block_number(n);
+			Ethereum::on_finalize(n);
+		}
+		System::set_block_number(3);
+
+		let (output, _) = Ethereum::call(
+			caller,
+			contract,
+			Vec::new(),
+			U256::zero(),
+			U256::from(100_000),
+			U256::one(),
+			None,
+		).unwrap();
+
+		assert_ne!(BlockNumbers::<Test>::get(1), H256::default());
+		assert_eq!(output, BlockNumbers::<Test>::get(1).as_bytes().to_vec());
+	});
+}
+
+/// Build a chain whose first block is shared and whose second block contains `transaction`,
+/// returning the hash of the second block.
+fn build_branch(ext: &mut sp_io::TestExternalities, sender: H160, transaction: Transaction) -> H256 {
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		Ethereum::on_finalize(1);
+
+		System::set_block_number(2);
+		assert_ok!(Ethereum::execute(sender, transaction));
+		Ethereum::on_finalize(2);
+		BlockNumbers::<Test>::get(2)
+	})
+}
+
+/// Check every index against `head` and its `transaction`, and that the competing
+/// `orphan` block and its `orphaned` transaction are unknown.
+fn assert_canonical(
+	ext: &mut sp_io::TestExternalities,
+	head: H256,
+	transaction: H256,
+	orphan: H256,
+	orphaned: H256,
+) {
+	ext.execute_with(|| {
+		assert_eq!(Ethereum::block_by_number(2).map(|block| block.header.number), Some(U256::from(2)));
+		assert_eq!(Ethereum::block_hash(2), head);
+		assert_eq!(Ethereum::block_transaction_hashes(head), Some(vec![transaction]));
+		assert_eq!(
+			Ethereum::transaction_status(transaction).map(|status| status.transaction_hash),
+			Some(transaction)
+		);
+		assert_eq!(
+			Ethereum::transaction_by_hash(transaction).map(|(_, block, _)| block.header.number),
+			Some(U256::from(2))
+		);
+
+		assert_ne!(Ethereum::block_hash(2), orphan);
+		assert!(Ethereum::block_by_hash(orphan).is_none());
+		assert!(Ethereum::block_transaction_hashes(orphan).is_none());
+		assert!(Ethereum::transaction_status(orphaned).is_none());
+		assert!(Ethereum::transaction_by_hash(orphaned).is_none());
+	});
+}
+
+#[test]
+fn indexes_should_follow_the_canonical_branch() {
+	let (pairs, mut a) = new_test_ext(1);
+	let (_, mut b) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	let transfer = transfer_transaction(alice, G_TRANSACTION);
+	let transfer_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transfer)).as_slice());
+	let create = default_erc20_creation_transaction(alice);
+	let create_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&create)).as_slice());
+
+	let head_a = build_branch(&mut a, alice.address, transfer);
+	let head_b = build_branch(&mut b, alice.address, create);
+	assert_ne!(head_a, head_b);
+
+	// Switch from branch A to branch B and back again.
+	assert_canonical(&mut a, head_a, transfer_hash, head_b, create_hash);
+	assert_canonical(&mut b, head_b, create_hash, head_a, transfer_hash);
+	assert_canonical(&mut a, head_a, transfer_hash, head_b, create_hash);
+}
+
+#[test]
+fn blocks_in_range_should_match_individual_blocks() {
+	let (_pairs, mut ext) = new_test_ext(1);
+
+	ext.execute_with(|| {
+		for n in 1..=4 {
+			System::set_block_number(n);
+			Ethereum::on_finalize(n);
+		}
+
+		let individual = (2..=4)
+			.map(|n| Ethereum::block_by_number(n).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(Ethereum::blocks_in_range(2, 4), individual);
+		assert_eq!(Ethereum::blocks_in_range(3, 9).len(), 2);
+		assert!(Ethereum::blocks_in_range(4, 3).is_empty());
+	});
+}
+
+#[test]
+fn blocks_in_range_should_be_bounded() {
+	let (_pairs, mut ext) = new_test_ext(1);
+
+	ext.execute_with(|| {
+		let last = MAX_BLOCKS_IN_RANGE as u64 + 2;
+		for n in 1..=last {
+			System::set_block_number(n);
+			Ethereum::on_finalize(n);
+		}
+
+		let blocks = Ethereum::blocks_in_range(1, last);
+		assert_eq!(blocks.len(), MAX_BLOCKS_IN_RANGE as usize);
+		assert_eq!(blocks.last().map(|block| block.header.number), Some(U256::from(MAX_BLOCKS_IN_RANGE)));
+	});
+}
+
+#[test]
+fn signature_v_should_decode_chain_id_and_parity() {
+	assert_eq!(decode_v(27), Some((None, 0)));
+	assert_eq!(decode_v(28), Some((None, 1)));
+	assert_eq!(decode_v(37), Some((Some(1), 0)));
+	assert_eq!(decode_v(0x78), Some((Some(42), 1)));
+	assert_eq!(decode_v(0), None);
+	assert_eq!(decode_v(29), None);
+}
+
+#[test]
+fn signer_should_be_recovered_for_any_chain_id() {
+	let (pairs, mut ext) = new_test_ext(2);
+
+	ext.execute_with(|| {
+		for pair in &pairs {
+			for chain_id in &[1u64, 42, 1_000_000] {
+				let transaction = chain_transfer_transaction(pair, *chain_id);
+				assert_eq!(recover_signer(&transaction), Some((pair.address, Some(*chain_id))));
+			}
+		}
+	});
+}
+
+#[test]
+fn transaction_for_another_chain_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = chain_transfer_transaction(alice, 1);
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Custom(TransactionValidationError::InvalidChainId as u8).into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::InvalidChainId
+		);
+	});
+}
+
+#[test]
+fn transaction_should_be_consistent_from_pool_to_receipt() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let mut ext = funded_ext(&[alice.address]);
+
+	ext.execute_with(|| {
+		let transaction = erc20_creation_transaction(alice, U256::one());
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+
+		// Pool import.
+		let call = Call::transact(transaction.clone());
+		assert_ok!(Ethereum::validate_unsigned(TransactionSource::External, &call));
+
+		// Block build.
+		System::set_block_number(1);
+		assert_ok!(Ethereum::transact(Origin::none(), transaction.clone()));
+		Ethereum::on_finalize(1);
+
+		// Transaction and receipt queries.
+		let block_hash = BlockNumbers::<Test>::get(1);
+		let (found, block, status) = Ethereum::transaction_by_hash(hash).unwrap();
+		assert_eq!(found, transaction);
+		assert_eq!(
+			H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice()),
+			block_hash
+		);
+		assert_eq!(block.header.number, U256::from(1));
+		assert_eq!(block.transactions[status.transaction_index as usize], transaction);
+		assert_eq!(Ethereum::block_transaction_hashes(block_hash), Some(vec![hash]));
+
+		assert_eq!(status.transaction_hash, hash);
+		assert_eq!(status.transaction_index, 0);
+		assert_eq!(status.from, alice.address);
+		assert_eq!(status.contract_address, Some(contract_address(alice.address, 0)));
+		assert!(!status.gas_used.is_zero());
+		assert_eq!(status.cumulative_gas_used, status.gas_used);
+		assert_eq!(block.header.gas_used, status.cumulative_gas_used);
+		assert_eq!(Ethereum::transaction_status(hash), Some(status));
+	});
+}
+
+#[test]
+fn linked_account_should_resolve_from_its_address() {
+	let (_, mut ext) = new_test_ext(0);
+	let account = H160::repeat_byte(0x42);
+	let address = HashTruncateConvertAccountId::<BlakeTwo256>::convert_account_id(&account);
+
+	ext.execute_with(|| {
+		assert_eq!(Ethereum::substrate_account(address), None);
+
+		assert_ok!(Ethereum::link_account(Origin::signed(account)));
+
+		assert_eq!(Ethereum::substrate_account(address), Some(account));
+		assert_eq!(Ethereum::substrate_account(H160::repeat_byte(0x01)), None);
+	});
+}
+
+#[test]
+fn genesis_account_balance_should_be_reported() {
+	let address = H160::repeat_byte(0x42);
+	let mut accounts = BTreeMap::new();
+	accounts.insert(address, pallet_evm::GenesisAccount {
+		nonce: U256::from(3),
+		balance: U256::from(1_000_000),
+		storage: BTreeMap::new(),
+		code: Vec::new(),
+	});
+	let mut ext = genesis_ext(accounts);
+
+	ext.execute_with(|| {
+		let account = Evm::accounts(address);
+		assert_eq!(account.balance, U256::from(1_000_000));
+		assert_eq!(account.nonce, U256::from(3));
+		assert_eq!(Evm::accounts(H160::repeat_byte(0x01)).balance, U256::zero());
+	});
+}
+
+#[test]
+fn genesis_contract_should_report_code_hash_at_block_zero() {
+	let contract = H160::repeat_byte(0x42);
+	let code: Vec<u8> = FromHex::from_hex("60ff60005260206000f3").unwrap();
+	let mut accounts = BTreeMap::new();
+	accounts.insert(contract, pallet_evm::GenesisAccount {
+		nonce: U256::zero(),
+		balance: U256::zero(),
+		storage: BTreeMap::new(),
+		code: code.clone(),
+	});
+	let mut ext = genesis_ext(accounts);
+
+	ext.execute_with(|| {
+		assert_eq!(System::block_number(), 0);
+		assert_eq!(
+			Ethereum::account_code_hash(contract),
+			H256::from_slice(Keccak256::digest(&code).as_slice())
+		);
+		assert_eq!(
+			Ethereum::account_code_hash(H160::repeat_byte(0x01)),
+			H256::from_slice(Keccak256::digest(&[]).as_slice())
+		);
+	});
+}
+
+#[test]
+fn gas_price_should_be_fee_calculator_minimum() {
+	let (_, mut ext) = new_test_ext(0);
+
+	ext.execute_with(|| {
+		assert_eq!(Ethereum::gas_price(), U256::one());
+	});
+}
+
+/// Externalities with a funded `caller` and `code` deployed at `contract`.
+fn contract_ext(caller: H160, contract: H160, code: &str) -> sp_io::TestExternalities {
+	let mut accounts = BTreeMap::new();
+	accounts.insert(caller, pallet_evm::GenesisAccount {
+		nonce: U256::zero(),
+		balance: U256::from(1_000_000_000u64),
+		storage: BTreeMap::new(),
+		code: Vec::new(),
+	});
+	accounts.insert(contract, pallet_evm::GenesisAccount {
+		nonce: U256::zero(),
+		balance: U256::zero(),
+		storage: BTreeMap::new(),
+		code: FromHex::from_hex(code).unwrap(),
+	});
+	genesis_ext(accounts)
+}
+
+#[test]
+fn logs_emitted_by_transaction_should_be_stored() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let contract = H160::repeat_byte(0x42);
+	// `PUSH1 0x2a PUSH1 0 MSTORE PUSH1 1 PUSH1 32 PUSH1 0 LOG1 STOP`: logs the word 42
+	// with topic 1.
+	let mut ext = contract_ext(alice.address, contract, "602a600052600160206000a100");
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		let transaction = UnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::one(),
+			gas_limit: U256::from(100_000),
+			action: ethereum::TransactionAction::Call(contract),
+			value: U256::zero(),
+			input: Vec::new(),
+		}.sign(&alice.private_key);
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+		assert_ok!(Ethereum::transact(Origin::none(), transaction));
+		Ethereum::on_finalize(1);
+
+		let expected = Log {
+			address: contract,
+			topics: vec![H256::from_low_u64_be(1)],
+			data: H256::from_low_u64_be(0x2a).as_bytes().to_vec(),
+		};
+		let status = Ethereum::transaction_status(hash).unwrap();
+		assert_eq!(status.logs, vec![expected.clone()]);
+		assert_eq!(status.logs_bloom, Ethereum::logs_bloom(&[expected]));
+		let bloom = Ethereum::block_by_number(1).unwrap().header.logs_bloom;
+		assert!(bloom.contains_input(BloomInput::Raw(&contract[..])));
+	});
+}
+
+#[test]
+fn transfer_call_should_use_intrinsic_gas() {
+	let caller = H160::repeat_byte(0x01);
+	let mut ext = contract_ext(caller, H160::repeat_byte(0x42), "60ff60005260206000f3");
+
+	ext.execute_with(|| {
+		let (output, gas_used) = Ethereum::call(
+			caller,
+			H160::repeat_byte(0x02),
+			Vec::new(),
+			U256::from(1),
+			U256::from(100_000),
+			U256::one(),
+			None,
+		).unwrap();
+
+		assert_eq!(output, Vec::<u8>::new());
+		assert_eq!(gas_used, U256::from(21_000));
+		assert_eq!(Evm::accounts(H160::repeat_byte(0x02)).balance, U256::zero());
+	});
+}
+
+#[test]
+fn contract_call_should_use_execution_gas() {
+	let caller = H160::repeat_byte(0x01);
+	let contract = H160::repeat_byte(0x42);
+	let mut ext = contract_ext(caller, contract, "60ff60005260206000f3");
+
+	ext.execute_with(|| {
+		let (_, gas_used) = Ethereum::call(
+			caller,
+			contract,
+			Vec::new(),
+			U256::zero(),
+			U256::from(100_000),
+			U256::one(),
+			None,
+		).unwrap();
+
+		assert!(gas_used > U256::from(21_000));
+		assert!(gas_used < U256::from(100_000));
+	});
+}
+
+#[test]
+fn call_should_return_getter_output_without_persisting() {
+	let caller = H160::repeat_byte(0x01);
+	let contract = H160::repeat_byte(0x42);
+	// `PUSH1 0xff PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`
+	let mut ext = contract_ext(caller, contract, "60ff60005260206000f3");
+
+	ext.execute_with(|| {
+		let (output, _) = Ethereum::call(
+			caller,
+			contract,
+			Vec::new(),
+			U256::zero(),
+			U256::from(100_000),
+			U256::one(),
+			None,
+		).unwrap();
+
+		assert_eq!(U256::from_big_endian(&output), U256::from(0xff));
+		assert_eq!(Evm::accounts(caller).nonce, U256::zero());
+		assert_eq!(Evm::accounts(caller).balance, U256::from(1_000_000_000u64));
+	});
+}
+
+#[test]
+fn call_from_an_empty_account_should_not_be_charged() {
+	let caller = H160::repeat_byte(0x07);
+	let contract = H160::repeat_byte(0x42);
+	let mut ext = contract_ext(H160::repeat_byte(0x01), contract, "60ff60005260206000f3");
+
+	ext.execute_with(|| {
+		let (output, _) = Ethereum::call(
+			caller,
+			contract,
+			Vec::new(),
+			U256::zero(),
+			U256::max_value(),
+			Ethereum::gas_price(),
+			None,
+		).unwrap();
+
+		assert_eq!(U256::from_big_endian(&output), U256::from(0xff));
+		assert_eq!(Evm::accounts(caller).balance, U256::zero());
+	});
+}
+
+#[test]
+fn create_should_return_deployed_code_without_persisting() {
+	let caller = H160::repeat_byte(0x01);
+	let mut ext = contract_ext(caller, H160::repeat_byte(0x42), "60ff60005260206000f3");
+	// Init code returning the runtime code `PUSH1 0xff PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`.
+	let runtime_code: Vec<u8> = FromHex::from_hex("60ff60005260206000f3").unwrap();
+	let init_code: Vec<u8> = FromHex::from_hex("600a600c600039600a6000f3").unwrap();
+	let data = [init_code, runtime_code.clone()].concat();
+
+	ext.execute_with(|| {
+		let (output, gas_used) = Ethereum::create(
+			caller,
+			data.clone(),
+			U256::zero(),
+			U256::from(100_000),
+			U256::one(),
+			None,
+		).unwrap();
+
+		assert_eq!(output, runtime_code);
+		assert!(gas_used > U256::from(G_TRANSACTION + G_TXCREATE));
+		assert_eq!(Evm::account_codes(contract_address(caller, 0)), Vec::<u8>::new());
+		assert_eq!(Evm::accounts(caller).nonce, U256::zero());
+
+		let result = Ethereum::create(
+			caller,
+			data,
+			U256::zero(),
+			gas_used - 1,
+			U256::one(),
+			None,
+		);
+		assert_eq!(result, Err(ExecutionError::OutOfGas));
+	});
+}
+
+#[test]
+fn reverting_call_should_return_revert_data() {
+	let caller = H160::repeat_byte(0x01);
+	let contract = H160::repeat_byte(0x42);
+	// `PUSH1 0xaa PUSH1 0 MSTORE PUSH1 32 PUSH1 0 REVERT`
+	let mut ext = contract_ext(caller, contract, "60aa60005260206000fd");
+
+	ext.execute_with(|| {
+		let result = Ethereum::call(
+			caller,
+			contract,
+			Vec::new(),
+			U256::zero(),
+			U256::from(100_000),
+			U256::one(),
+			None,
+		);
+
+		let mut expected = vec![0u8; 32];
+		expected[31] = 0xaa;
+		assert_eq!(result, Err(ExecutionError::Reverted(expected)));
+	});
+}
+
+#[test]
+fn raw_signed_transaction_should_be_accepted_by_pool() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let raw = rlp::encode(&default_erc20_creation_transaction(alice));
+		let transaction: Transaction = rlp::decode(&raw).unwrap();
+
+		let call = Call::transact(transaction);
+		assert_ok!(Ethereum::validate_unsigned(TransactionSource::External, &call));
+	});
+}
+
+#[test]
+fn block_logs_bloom_should_contain_log_addresses_and_topics() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	let log = Log {
+		address: H160::repeat_byte(0x11),
+		topics: vec![H256::repeat_byte(0x22), H256::repeat_byte(0x33)],
+		data: Vec::new(),
+	};
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		Ethereum::store_transaction(
+			alice.address, transfer_transaction(alice, 21_000), None, None, U256::zero(), vec![log],
+		);
+		Ethereum::on_finalize(1);
+
+		let bloom = Ethereum::block_by_number(1).unwrap().header.logs_bloom;
+		assert!(bloom.contains_input(BloomInput::Raw(&H160::repeat_byte(0x11)[..])));
+		assert!(bloom.contains_input(BloomInput::Raw(&H256::repeat_byte(0x22)[..])));
+		assert!(bloom.contains_input(BloomInput::Raw(&H256::repeat_byte(0x33)[..])));
+		assert!(!bloom.contains_input(BloomInput::Raw(&H160::repeat_byte(0x44)[..])));
+	});
+}
+
+#[test]
+fn transaction_above_block_gas_limit_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = transfer_transaction(alice, BlockGasLimit::get().low_u64() + 1);
+
+		let call = Call::transact(transaction.clone());
+		assert_eq!(
+			Ethereum::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::BlockGasLimitExceeded
+		);
+	});
+}
+
+#[test]
+fn transaction_should_not_fit_after_block_is_full() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		let first = transfer_transaction(alice, 21_000);
+		Ethereum::store_transaction(
+			alice.address, first, None, None, BlockGasLimit::get() - U256::from(20_000), Vec::new(),
+		);
+
+		let mut second = transfer_transaction(alice, 21_000);
+		second.nonce = U256::from(1);
+		let call = Call::transact(second);
+		assert_eq!(
+			Ethereum::validate_unsigned(TransactionSource::InBlock, &call),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+
+		Ethereum::on_finalize(1);
+		assert_eq!(Ethereum::block_by_number(1).unwrap().header.gas_limit, BlockGasLimit::get());
+	});
+}
+
+#[test]
+fn transaction_without_chain_id_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = UnsignedTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas_limit: U256::from(G_TRANSACTION),
+			action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+			value: U256::zero(),
+			input: Vec::new(),
+		}.sign_without_chain_id(&alice.private_key);
+		assert_eq!(recover_signer(&transaction), Some((alice.address, None)));
+
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Custom(TransactionValidationError::InvalidChainId as u8).into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::InvalidChainId
+		);
+	});
+}
+
+#[cfg(feature = "eip1559")]
+fn dynamic_fee_transaction() -> eip1559::DynamicFeeTransaction {
+	eip1559::DynamicFeeTransaction {
+		chain_id: ChainId::get(),
+		nonce: U256::from(3),
+		max_priority_fee_per_gas: U256::from(2),
+		max_fee_per_gas: U256::from(10),
+		gas_limit: U256::from(G_TRANSACTION),
+		action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+		value: U256::from(5),
+		input: vec![0xab, 0xcd],
+		access_list: vec![eip1559::AccessListItem {
+			address: H160::repeat_byte(0x22),
+			storage_keys: vec![H256::repeat_byte(0x01), H256::repeat_byte(0x02)],
+		}],
+		odd_y_parity: true,
+		r: H256::repeat_byte(0x33),
+		s: H256::repeat_byte(0x44),
+	}
+}
+
+#[cfg(feature = "eip1559")]
+#[test]
+fn dynamic_fee_transaction_should_round_trip_through_rlp() {
+	let transaction = eip1559::TypedTransaction::EIP1559(dynamic_fee_transaction());
+
+	let encoded = transaction.encode();
+
+	assert_eq!(encoded[0], eip1559::DYNAMIC_FEE_TRANSACTION_TYPE);
+	assert_eq!(eip1559::TypedTransaction::decode(&encoded), Ok(transaction));
+}
+
+#[cfg(feature = "eip1559")]
+#[test]
+fn legacy_transaction_should_round_trip_through_typed_envelope() {
+	let (pairs, _) = new_test_ext(1);
+	let transaction = eip1559::TypedTransaction::Legacy(transfer_transaction(&pairs[0], 21_000));
+
+	let encoded = transaction.encode();
+
+	assert_eq!(encoded, rlp::encode(&transfer_transaction(&pairs[0], 21_000)));
+	assert_eq!(eip1559::TypedTransaction::decode(&encoded), Ok(transaction));
+	assert!(eip1559::TypedTransaction::decode(&[0x01, 0xc0]).is_err());
+}
+
+#[cfg(feature = "eip1559")]
+#[test]
+fn effective_gas_price_should_be_capped_by_max_fee() {
+	let transaction = dynamic_fee_transaction();
+
+	assert_eq!(transaction.effective_gas_price(U256::from(5)), Some(U256::from(7)));
+	assert_eq!(transaction.effective_gas_price(U256::from(9)), Some(U256::from(10)));
+	assert_eq!(transaction.effective_gas_price(U256::from(11)), None);
+}
+
+#[test]
+fn effective_gas_price_should_require_max_fee_to_cover_base_fee() {
+	let price = |max_fee: u64, max_priority_fee: u64, base_fee: u64| effective_gas_price(
+		U256::from(max_fee),
+		U256::from(max_priority_fee),
+		U256::from(base_fee),
+	);
+
+	assert_eq!(price(10, 2, 5), Some(U256::from(7)));
+	assert_eq!(price(10, 2, 9), Some(U256::from(10)));
+	assert_eq!(price(10, 2, 10), Some(U256::from(10)));
+	assert_eq!(price(10, 2, 11), None);
+	// Legacy transactions offer their gas price as both.
+	assert_eq!(price(3, 3, 1), Some(U256::from(3)));
+}
+
+#[test]
+fn transaction_below_base_fee_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		let transaction = priced_transfer_transaction(alice, U256::zero());
+		assert_eq!(Ethereum::effective_gas_price(&transaction), None);
+		assert_eq!(
+			Ethereum::validate_unsigned(
+				TransactionSource::External,
+				&Call::transact(transaction.clone()),
+			),
+			InvalidTransaction::Custom(TransactionValidationError::GasPriceTooLow as u8).into()
+		);
+		assert_noop!(
+			Ethereum::transact(Origin::none(), transaction),
+			Error::<Test>::GasPriceTooLow
+		);
+	});
+}
+
+fn claim_signature(key: &H256, account: &H160) -> H520 {
+	let hash = Ethereum::claim_message_hash(account);
+	let (signature, recovery_id) = secp256k1::sign(
+		&secp256k1::Message::parse(hash.as_fixed_bytes()),
+		&secp256k1::SecretKey::parse_slice(&key[..]).unwrap(),
+	);
+	let mut bytes = [0u8; 65];
+	bytes[..64].copy_from_slice(&signature.serialize());
+	bytes[64] = recovery_id.serialize();
+	H520::from(bytes)
+}
+
+type ClaimedAddresses = StoredAddressMapping<Test, HashTruncateConvertAccountId<BlakeTwo256>>;
+
+#[test]
+fn claimed_address_should_map_to_its_account() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	let account = H160::repeat_byte(0x42);
+
+	ext.execute_with(|| {
+		let derived = HashTruncateConvertAccountId::<BlakeTwo256>::convert_account_id(&account);
+		assert_eq!(ClaimedAddresses::convert_account_id(&account), derived);
+
+		assert_ok!(Ethereum::claim_address(
+			Origin::signed(account),
+			alice.address,
+			claim_signature(&alice.private_key, &account),
+		));
+
+		assert_eq!(ClaimedAddresses::convert_account_id(&account), alice.address);
+		assert_eq!(Ethereum::evm_address(account), Some(alice.address));
+		assert_eq!(Ethereum::substrate_account(alice.address), Some(account));
+	});
+}
+
+#[test]
+fn claim_without_key_signature_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(2);
+	let account = H160::repeat_byte(0x42);
+
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::claim_address(
+				Origin::signed(account),
+				pairs[0].address,
+				claim_signature(&pairs[1].private_key, &account),
+			),
+			Error::<Test>::InvalidAddressSignature
+		);
+		assert_noop!(
+			Ethereum::claim_address(
+				Origin::signed(account),
+				pairs[0].address,
+				claim_signature(&pairs[0].private_key, &H160::repeat_byte(0x43)),
+			),
+			Error::<Test>::InvalidAddressSignature
+		);
+	});
+}
+
+#[test]
+fn duplicate_claims_should_be_rejected() {
+	let (pairs, mut ext) = new_test_ext(2);
+	let (alice, bob) = (&pairs[0], &pairs[1]);
+	let first = H160::repeat_byte(0x42);
+	let second = H160::repeat_byte(0x43);
+
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::claim_address(
+			Origin::signed(first),
+			alice.address,
+			claim_signature(&alice.private_key, &first),
+		));
+
+		assert_noop!(
+			Ethereum::claim_address(
+				Origin::signed(second),
+				alice.address,
+				claim_signature(&alice.private_key, &second),
+			),
+			Error::<Test>::AddressAlreadyLinked
+		);
+		assert_noop!(
+			Ethereum::claim_address(
+				Origin::signed(first),
+				bob.address,
+				claim_signature(&bob.private_key, &first),
+			),
+			Error::<Test>::AddressAlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn claim_should_be_rejected_while_previous_address_holds_balance() {
+	let (pairs, _) = new_test_ext(1);
+	let alice = &pairs[0];
+	let account = H160::repeat_byte(0x42);
+	let derived = HashTruncateConvertAccountId::<BlakeTwo256>::convert_account_id(&account);
+	let mut ext = funded_ext(&[derived]);
+
+	ext.execute_with(|| {
+		assert_noop!(
+			Ethereum::claim_address(
+				Origin::signed(account),
+				alice.address,
+				claim_signature(&alice.private_key, &account),
+			),
+			Error::<Test>::PreviousAddressNotEmpty
+		);
+	});
+}
+
+#[test]
+fn claim_should_replace_link_of_previous_address() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+	let account = H160::repeat_byte(0x42);
+
+	ext.execute_with(|| {
+		let derived = HashTruncateConvertAccountId::<BlakeTwo256>::convert_account_id(&account);
+		assert_ok!(Ethereum::link_account(Origin::signed(account)));
+		assert_ok!(Ethereum::claim_address(
+			Origin::signed(account),
+			alice.address,
+			claim_signature(&alice.private_key, &account),
+		));
+
+		assert_eq!(Ethereum::substrate_account(derived), None);
+		assert_eq!(Ethereum::substrate_account(alice.address), Some(account));
+		assert_noop!(
+			Ethereum::link_account(Origin::signed(account)),
+			Error::<Test>::AddressAlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn genesis_contract_should_have_code_and_storage() {
+	let contract = H160::repeat_byte(0x42);
+	let code: Vec<u8> = FromHex::from_hex("60ff60005260206000f3").unwrap();
+	let mut storage = BTreeMap::new();
+	storage.insert(H256::from_low_u64_be(1), H256::from_low_u64_be(0x2a));
+	let mut accounts = BTreeMap::new();
+	accounts.insert(contract, pallet_evm::GenesisAccount {
+		nonce: U256::one(),
+		balance: U256::from(100),
+		storage,
+		code: code.clone(),
+	});
+	let mut ext = genesis_ext(accounts);
+
+	ext.execute_with(|| {
+		assert_eq!(Evm::account_codes(contract), code);
+		assert_eq!(Ethereum::storage_at(contract, U256::from(1)), H256::from_low_u64_be(0x2a));
+		assert_eq!(Ethereum::storage_at(contract, U256::from(2)), H256::default());
+		assert_eq!(Evm::accounts(contract).nonce, U256::one());
+		assert_eq!(Evm::accounts(contract).balance, U256::from(100));
+	});
+}
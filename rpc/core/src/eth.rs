@@ -21,7 +21,7 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
 use crate::types::{
-	BlockNumber, Bytes, CallRequest, EthAccount, Filter, FilterChanges, Index, Log, Receipt,
+	BlockNumber, Bytes, CallRequest, EthAccount, FeeHistory, Filter, FilterChanges, Index, Log, Receipt,
 	RichBlock, SyncStatus, Transaction, Work,
 };
 pub use rpc_impl_EthApi::gen_server::EthApi as EthApiServer;
@@ -59,6 +59,10 @@ pub trait EthApi {
 	#[rpc(name = "eth_gasPrice")]
 	fn gas_price(&self) -> Result<U256>;
 
+	/// Returns fee history over the given number of blocks up to the newest one.
+	#[rpc(name = "eth_feeHistory")]
+	fn fee_history(&self, _: U256, _: BlockNumber, _: Option<Vec<f64>>) -> Result<FeeHistory>;
+
 	/// Returns accounts list.
 	#[rpc(name = "eth_accounts")]
 	fn accounts(&self) -> Result<Vec<H160>>;
@@ -111,6 +115,14 @@ pub trait EthApi {
 	#[rpc(name = "eth_getCode")]
 	fn code_at(&self, _: H160, _: Option<BlockNumber>) -> Result<Bytes>;
 
+	/// Returns the size of the code at given address at given time (block number).
+	#[rpc(name = "eth_getCodeSize")]
+	fn code_size(&self, _: H160, _: Option<BlockNumber>) -> Result<U256>;
+
+	/// Returns the Substrate account linked to given address at given time (block number).
+	#[rpc(name = "eth_getSubstrateAccount")]
+	fn substrate_account(&self, _: H160, _: Option<BlockNumber>) -> Result<Option<H256>>;
+
 	/// Sends signed transaction, returning its hash.
 	#[rpc(name = "eth_sendRawTransaction")]
 	fn send_raw_transaction(&self, _: Bytes) -> BoxFuture<H256>;
@@ -151,6 +163,10 @@ pub trait EthApi {
 	#[rpc(name = "eth_getTransactionReceipt")]
 	fn transaction_receipt(&self, _: H256) -> Result<Option<Receipt>>;
 
+	/// Returns the transactions in the pool that are ready to be mined.
+	#[rpc(name = "eth_pendingTransactions")]
+	fn pending_transactions(&self) -> Result<Vec<Transaction>>;
+
 	/// Returns an uncles at given block and index.
 	#[rpc(name = "eth_getUncleByBlockHashAndIndex")]
 	fn uncle_by_block_hash_and_index(&self, _: H256, _: Index) -> Result<Option<RichBlock>>;
@@ -198,12 +214,6 @@ pub trait EthApi {
 	/// Used for submitting mining hashrate.
 	#[rpc(name = "eth_submitHashrate")]
 	fn submit_hashrate(&self, _: U256, _: H256) -> Result<bool>;
-
-	// TODO: Once development is more stable, remove the net_ functions which are already defined net.rs
-	#[rpc(name = "net_listening")]
-	fn is_listening(&self) -> Result<bool>;
-	#[rpc(name = "net_version")]
-	fn version(&self) -> Result<String>;
 }
 
 /// Eth filters rpc api (polling).
@@ -16,31 +16,35 @@
 
 //! Eth rpc interface.
 
-use jsonrpc_core::BoxFuture;
+use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 
 use ethereum_types::{H160, H256, H520};
-use crate::types::{Bytes, TransactionRequest, RichRawTransaction};
+use crate::types::{Bytes, TransactionRequest, RichRawTransaction, TypedData};
 
-/// Signing methods implementation relying on unlocked accounts.
+pub use rpc_impl_EthSigningApi::gen_server::EthSigningApi as EthSigningApiServer;
+
+/// Signing methods implementation relying on keys held by the node.
 #[rpc(server)]
 pub trait EthSigningApi {
-	/// RPC Metadata
-	type Metadata;
+	/// Signs data prefixed with the Ethereum personal message header using the key
+	/// of the given address.
+	#[rpc(name = "eth_sign")]
+	fn sign(&self, _: H160, _: Bytes) -> Result<H520>;
 
-	/// Signs the hash of data with given address signature.
-	#[rpc(meta, name = "eth_sign")]
-	fn sign(&self, _: Self::Metadata, _: H160, _: Bytes) -> BoxFuture<H520>;
+	/// Signs EIP-712 typed data using the key of the given address.
+	#[rpc(name = "eth_signTypedData_v4")]
+	fn sign_typed_data(&self, _: H160, _: TypedData) -> Result<H520>;
 
 	/// Sends transaction; will block waiting for signer to return the
 	/// transaction hash.
 	/// If Signer is disable it will require the account to be unlocked.
-	#[rpc(meta, name = "eth_sendTransaction")]
-	fn send_transaction(&self, _: Self::Metadata, _: TransactionRequest) -> BoxFuture<H256>;
+	#[rpc(name = "eth_sendTransaction")]
+	fn send_transaction(&self, _: TransactionRequest) -> Result<H256>;
 
 	/// Signs transactions without dispatching it to the network.
 	/// Returns signed transaction RLP representation and the transaction itself.
 	/// It can be later submitted using `eth_sendRawTransaction/eth_submitTransaction`.
-	#[rpc(meta, name = "eth_signTransaction")]
-	fn sign_transaction(&self, _: Self::Metadata, _: TransactionRequest) -> BoxFuture<RichRawTransaction>;
+	#[rpc(name = "eth_signTransaction")]
+	fn sign_transaction(&self, _: TransactionRequest) -> Result<RichRawTransaction>;
 }
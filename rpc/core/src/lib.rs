@@ -20,10 +20,12 @@ mod eth;
 mod eth_pubsub;
 mod eth_signing;
 mod net;
+mod txpool;
 mod web3;
 
 pub use eth::{EthApi, EthApiServer, EthFilterApi};
-pub use eth_pubsub::EthPubSubApi;
-pub use eth_signing::EthSigningApi;
-pub use net::NetApi;
+pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer};
+pub use eth_signing::{EthSigningApi, EthSigningApiServer};
+pub use net::{NetApi, NetApiServer};
+pub use txpool::{TxPoolApi, TxPoolApiServer};
 pub use web3::Web3Api;
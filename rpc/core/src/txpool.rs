@@ -0,0 +1,35 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool rpc interface.
+use ethereum_types::U256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use crate::types::{TransactionMap, TxPoolResult};
+pub use rpc_impl_TxPoolApi::gen_server::TxPoolApi as TxPoolApiServer;
+
+/// Transaction pool rpc interface.
+#[rpc(server)]
+pub trait TxPoolApi {
+	/// Returns the pending and queued transactions of the pool, grouped by sender and nonce.
+	#[rpc(name = "txpool_content")]
+	fn content(&self) -> Result<TxPoolResult<TransactionMap>>;
+
+	/// Returns the number of pending and queued transactions in the pool.
+	#[rpc(name = "txpool_status")]
+	fn status(&self) -> Result<TxPoolResult<U256>>;
+}
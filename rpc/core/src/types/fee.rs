@@ -0,0 +1,33 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethereum_types::U256;
+use serde::Serialize;
+
+/// Fee history over a range of blocks, as returned by `eth_feeHistory`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+	/// Number of the oldest block in the range.
+	pub oldest_block: U256,
+	/// Base fee per gas of each block, followed by the one of the next block.
+	pub base_fee_per_gas: Vec<U256>,
+	/// Share of its gas limit each block used.
+	pub gas_used_ratio: Vec<f64>,
+	/// Priority fees paid at the requested percentiles, per block.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub reward: Option<Vec<Vec<U256>>>,
+}
@@ -47,6 +47,19 @@ impl<'a, T> Deserialize<'a> for VariadicValue<T> where T: DeserializeOwned {
 	}
 }
 
+impl<T> VariadicValue<T> where T: DeserializeOwned + PartialEq {
+	/// Whether `value` is accepted, where null and empty lists accept anything and a
+	/// list accepts any of its entries.
+	pub fn matches(&self, value: Option<&T>) -> bool {
+		match self {
+			VariadicValue::Null => true,
+			VariadicValue::Multiple(values) if values.is_empty() => true,
+			VariadicValue::Single(expected) => value == Some(expected),
+			VariadicValue::Multiple(values) => value.map_or(false, |value| values.contains(value)),
+		}
+	}
+}
+
 /// Filter Address
 pub type FilterAddress = VariadicValue<H160>;
 /// Topic
@@ -21,6 +21,7 @@ mod block;
 mod block_number;
 mod bytes;
 mod call_request;
+mod fee;
 mod filter;
 mod index;
 mod log;
@@ -29,6 +30,8 @@ mod sync;
 mod transaction;
 mod transaction_request;
 mod transaction_condition;
+mod txpool;
+mod typed_data;
 mod work;
 
 pub mod pubsub;
@@ -38,7 +41,8 @@ pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::BlockNumber;
 pub use self::call_request::CallRequest;
-pub use self::filter::{Filter, FilterChanges};
+pub use self::fee::FeeHistory;
+pub use self::filter::{Filter, FilterChanges, FilterAddress, Topic, VariadicValue};
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::receipt::Receipt;
@@ -49,4 +53,6 @@ pub use self::sync::{
 pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
+pub use self::txpool::{TransactionMap, TxPoolResult};
+pub use self::typed_data::{TypedData, TypedDataField};
 pub use self::work::Work;
@@ -0,0 +1,33 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use ethereum_types::{H160, U256};
+use crate::types::Transaction;
+
+/// Transactions grouped by sender and nonce.
+pub type TransactionMap = BTreeMap<H160, BTreeMap<U256, Transaction>>;
+
+/// Transaction pool result, split into pending and queued parts.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TxPoolResult<T> {
+	/// Transactions ready to be included in a block.
+	pub pending: T,
+	/// Transactions waiting for an earlier nonce.
+	pub queued: T,
+}
@@ -0,0 +1,77 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::Value;
+
+/// A named member of an EIP-712 struct type.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TypedDataField {
+	/// Member name
+	pub name: String,
+	/// Member type
+	#[serde(rename = "type")]
+	pub type_: String,
+}
+
+/// EIP-712 typed data, as passed to `eth_signTypedData_v4`. Accepted either as a
+/// JSON object or as a JSON-encoded string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedData {
+	/// Struct type definitions, including `EIP712Domain`
+	pub types: BTreeMap<String, Vec<TypedDataField>>,
+	/// Type of `message`
+	pub primary_type: String,
+	/// Domain separator values
+	pub domain: Value,
+	/// Message values
+	pub message: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypedDataObject {
+	types: BTreeMap<String, Vec<TypedDataField>>,
+	primary_type: String,
+	domain: Value,
+	message: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TypedDataParam {
+	Object(TypedDataObject),
+	Encoded(String),
+}
+
+impl<'a> Deserialize<'a> for TypedData {
+	fn deserialize<D>(deserializer: D) -> Result<TypedData, D::Error> where D: Deserializer<'a> {
+		let object = match TypedDataParam::deserialize(deserializer)? {
+			TypedDataParam::Object(object) => object,
+			TypedDataParam::Encoded(json) => serde_json::from_str(&json).map_err(D::Error::custom)?,
+		};
+
+		Ok(TypedData {
+			types: object.types,
+			primary_type: object.primary_type,
+			domain: object.domain,
+			message: object.message,
+		})
+	}
+}
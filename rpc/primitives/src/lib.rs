@@ -21,9 +21,30 @@ use ethereum::{Log, Block as EthereumBlock, Transaction as EthereumTransaction};
 use ethereum_types::Bloom;
 use codec::{Encode, Decode};
 use sp_std::vec::Vec;
+use sp_runtime::{AccountId32, traits::Block as BlockT};
+
+/// Maximum number of blocks returned by a single `blocks_in_range` runtime call.
+pub const MAX_BLOCKS_IN_RANGE: u32 = 64;
 
 #[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
 pub struct TransactionStatus {
+	pub transaction_hash: H256,
+	pub transaction_index: u32,
+	pub from: H160,
+	pub to: Option<H160>,
+	pub contract_address: Option<H160>,
+	pub gas_used: U256,
+	pub cumulative_gas_used: U256,
+	/// Index of the first log of this transaction among all logs of its block.
+	pub log_index: u32,
+	pub logs: Vec<Log>,
+	pub logs_bloom: Bloom,
+}
+
+/// `TransactionStatus` as returned by runtimes before version 5 of `EthereumRuntimeApi`,
+/// which track neither gas usage nor the position of logs in their block.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct LegacyTransactionStatus {
 	pub transaction_hash: H256,
 	pub transaction_index: u32,
 	pub from: H160,
@@ -33,16 +54,85 @@ pub struct TransactionStatus {
 	pub logs_bloom: Bloom,
 }
 
+impl From<LegacyTransactionStatus> for TransactionStatus {
+	fn from(status: LegacyTransactionStatus) -> Self {
+		TransactionStatus {
+			transaction_hash: status.transaction_hash,
+			transaction_index: status.transaction_index,
+			from: status.from,
+			to: status.to,
+			contract_address: status.contract_address,
+			gas_used: U256::zero(),
+			cumulative_gas_used: U256::zero(),
+			log_index: 0,
+			logs: status.logs,
+			logs_bloom: status.logs_bloom,
+		}
+	}
+}
+
+/// Split a signature `v` value into the chain id it commits to and the recovery parity.
+/// Legacy signatures use `27 + parity` and carry no chain id, EIP-155 signatures use
+/// `chain_id * 2 + 35 + parity`.
+pub fn decode_v(v: u64) -> Option<(Option<u64>, u8)> {
+	match v {
+		27 | 28 => Some((None, (v - 27) as u8)),
+		v if v >= 35 => Some((Some((v - 35) / 2), ((v - 35) % 2) as u8)),
+		_ => None,
+	}
+}
+
+/// Recover the sender of a transaction and the chain id it was signed for.
+pub fn recover_signer(transaction: &EthereumTransaction) -> Option<(H160, Option<u64>)> {
+	let (chain_id, parity) = decode_v(transaction.signature.v())?;
+
+	let mut sig = [0u8; 65];
+	sig[0..32].copy_from_slice(&transaction.signature.r()[..]);
+	sig[32..64].copy_from_slice(&transaction.signature.s()[..]);
+	sig[64] = parity;
+	let msg = transaction.message_hash(chain_id);
+
+	let pubkey = sp_io::crypto::secp256k1_ecdsa_recover(&sig, msg.as_fixed_bytes()).ok()?;
+	let signer = H160::from(H256::from(sp_io::hashing::keccak_256(&pubkey)));
+	Some((signer, chain_id))
+}
+
+/// Reason a read-only EVM execution did not succeed.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub enum ExecutionError {
+	/// Execution ran out of gas.
+	OutOfGas,
+	/// Execution reverted, carrying the revert output.
+	Reverted(Vec<u8>),
+	/// Execution failed for any other reason.
+	Failed,
+}
+
 sp_api::decl_runtime_apis! {
 	/// API necessary for Ethereum-compatibility layer.
+	///
+	/// Version 2 added `account_code_len`, `block_transaction_hashes` and
+	/// `extrinsic_filter`, and made `call` report why an execution failed.
+	/// Version 3 added `blocks_in_range`.
+	/// Version 4 added `substrate_account`.
+	/// Version 5 added gas usage and log positions to `TransactionStatus`.
+	/// Version 6 added `create`.
+	/// Version 7 added `transaction_status_retention`.
+	#[api_version(7)]
 	pub trait EthereumRuntimeApi {
 		fn chain_id() -> u64;
 		fn account_basic(address: H160) -> pallet_evm::Account;
+		#[changed_in(5)]
+		fn transaction_status(hash: H256) -> Option<LegacyTransactionStatus>;
 		fn transaction_status(hash: H256) -> Option<TransactionStatus>;
 		fn gas_price() -> U256;
 		fn account_code_at(address: H160) -> Vec<u8>;
+		fn account_code_len(address: H160) -> u64;
 		fn author() -> H160;
 		fn storage_at(address: H160, index: U256) -> H256;
+		/// Substrate account linked to an address, if any.
+		fn substrate_account(address: H160) -> Option<AccountId32>;
+		#[changed_in(2)]
 		fn call(
 			from: H160,
 			to: H160,
@@ -52,15 +142,53 @@ sp_api::decl_runtime_apis! {
 			gas_price: U256,
 			nonce: Option<U256>,
 		) -> Option<(Vec<u8>, U256)>;
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: U256,
+			nonce: Option<U256>,
+		) -> Result<(Vec<u8>, U256), ExecutionError>;
+		/// Execute a contract creation without applying it, returning the code it would
+		/// deploy and the gas it used.
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: U256,
+			nonce: Option<U256>,
+		) -> Result<(Vec<u8>, U256), ExecutionError>;
 		fn block_by_number(number: u32) -> Option<EthereumBlock>;
 		fn block_transaction_count_by_number(number: u32) -> Option<U256>;
 		fn block_by_hash(hash: H256) -> Option<EthereumBlock>;
+		/// Blocks `from..=to` in ascending order, scanning at most `MAX_BLOCKS_IN_RANGE`
+		/// numbers and skipping unknown ones.
+		fn blocks_in_range(from: u32, to: u32) -> Vec<EthereumBlock>;
 		fn block_transaction_count_by_hash(hash: H256) -> Option<U256>;
+		fn block_transaction_hashes(hash: H256) -> Option<Vec<H256>>;
+		#[changed_in(5)]
+		fn transaction_by_hash(hash: H256) -> Option<(
+			EthereumTransaction,
+			EthereumBlock,
+			LegacyTransactionStatus
+		)>;
 		fn transaction_by_hash(hash: H256) -> Option<(
 			EthereumTransaction,
 			EthereumBlock,
 			TransactionStatus
 		)>;
+		#[changed_in(5)]
+		fn transaction_by_block_hash_and_index(
+			hash: H256,
+			index: u32
+		) -> Option<(
+			EthereumTransaction,
+			EthereumBlock,
+			LegacyTransactionStatus
+		)>;
 		fn transaction_by_block_hash_and_index(
 			hash: H256,
 			index: u32
@@ -69,6 +197,15 @@ sp_api::decl_runtime_apis! {
 			EthereumBlock,
 			TransactionStatus
 		)>;
+		#[changed_in(5)]
+		fn transaction_by_block_number_and_index(
+			number: u32,
+			index: u32
+		) -> Option<(
+			EthereumTransaction,
+			EthereumBlock,
+			LegacyTransactionStatus
+		)>;
 		fn transaction_by_block_number_and_index(
 			number: u32,
 			index: u32
@@ -77,6 +214,11 @@ sp_api::decl_runtime_apis! {
 			EthereumBlock,
 			TransactionStatus
 		)>;
+		fn extrinsic_filter(
+			xts: Vec<<Block as BlockT>::Extrinsic>
+		) -> Vec<EthereumTransaction>;
+		/// Number of recent blocks whose transaction statuses are kept, zero for all.
+		fn transaction_status_retention() -> u32;
 	}
 }
 
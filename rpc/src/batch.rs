@@ -0,0 +1,106 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Server middleware limiting the size of JSON-RPC batches.
+
+use jsonrpc_core::{
+	Call, Error, ErrorCode, Metadata, Middleware, Output, Request, Response, Version,
+	futures::{Future, future::{self, Either}},
+};
+
+/// Number of calls allowed in a single batch unless configured otherwise.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 1024;
+
+/// Rejects batches of more than `max` calls as a whole, before any of them executes.
+#[derive(Clone, Debug)]
+pub struct BatchSizeLimit {
+	max: usize,
+}
+
+impl BatchSizeLimit {
+	pub fn new(max: usize) -> Self {
+		Self { max }
+	}
+}
+
+impl Default for BatchSizeLimit {
+	fn default() -> Self {
+		Self::new(DEFAULT_MAX_BATCH_SIZE)
+	}
+}
+
+impl<M: Metadata> Middleware<M> for BatchSizeLimit {
+	type Future = future::FutureResult<Option<Response>, ()>;
+	type CallFuture = jsonrpc_core::middleware::NoopCallFuture;
+
+	fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X> where
+		F: FnOnce(Request, M) -> X + Send,
+		X: Future<Item = Option<Response>, Error = ()> + Send + 'static,
+	{
+		match request {
+			Request::Batch(ref calls) if calls.len() > self.max => {
+				let error = Error {
+					code: ErrorCode::InvalidRequest,
+					message: format!("batch of {} calls exceeds the maximum of {}", calls.len(), self.max),
+					data: None,
+				};
+				Either::A(future::ok(Some(Response::from(error, Some(Version::V2)))))
+			},
+			request => Either::B(next(request, meta)),
+		}
+	}
+
+	fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X> where
+		F: FnOnce(Call, M) -> X + Send,
+		X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+	{
+		Either::B(next(call, meta))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpc_core::{MetaIoHandler, Value};
+
+	fn handler() -> MetaIoHandler<(), BatchSizeLimit> {
+		let mut io = MetaIoHandler::with_middleware(BatchSizeLimit::new(2));
+		io.add_method("ping", |_| Ok(Value::String("pong".to_string())));
+		io
+	}
+
+	fn batch(len: usize) -> String {
+		let calls = (0..len)
+			.map(|id| format!(r#"{{"jsonrpc":"2.0","method":"ping","params":[],"id":{}}}"#, id))
+			.collect::<Vec<_>>();
+		format!("[{}]", calls.join(","))
+	}
+
+	#[test]
+	fn batch_within_limit_should_execute() {
+		let response = handler().handle_request_sync(&batch(2), ()).unwrap();
+
+		assert_eq!(response.matches("pong").count(), 2);
+	}
+
+	#[test]
+	fn oversized_batch_should_be_rejected_wholesale() {
+		let response = handler().handle_request_sync(&batch(3), ()).unwrap();
+
+		assert!(!response.contains("pong"));
+		assert!(response.contains("batch of 3 calls exceeds the maximum of 2"));
+	}
+}
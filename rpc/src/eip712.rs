@@ -0,0 +1,174 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-712 typed structured data hashing.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::Value;
+use rustc_hex::FromHex;
+use sha3::{Keccak256, Digest};
+use frontier_rpc_core::types::{TypedData, TypedDataField};
+
+type Types = BTreeMap<String, Vec<TypedDataField>>;
+
+const DOMAIN_TYPE: &str = "EIP712Domain";
+
+fn keccak(data: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(data).as_slice())
+}
+
+/// Hash typed data as `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`.
+pub fn hash_typed_data(typed_data: &TypedData) -> Result<H256, String> {
+	let domain = hash_struct(DOMAIN_TYPE, &typed_data.domain, &typed_data.types)?;
+	let message = hash_struct(&typed_data.primary_type, &typed_data.message, &typed_data.types)?;
+
+	let mut data = vec![0x19, 0x01];
+	data.extend_from_slice(domain.as_bytes());
+	data.extend_from_slice(message.as_bytes());
+	Ok(keccak(&data))
+}
+
+fn hash_struct(name: &str, value: &Value, types: &Types) -> Result<H256, String> {
+	let fields = types.get(name).ok_or_else(|| format!("unknown type {}", name))?;
+	let value = value.as_object().ok_or_else(|| format!("expected object for type {}", name))?;
+
+	let mut data = keccak(encode_type(name, types)?.as_bytes()).as_bytes().to_vec();
+	for field in fields {
+		let member = value.get(&field.name).unwrap_or(&Value::Null);
+		data.extend_from_slice(encode_value(&field.type_, member, types)?.as_bytes());
+	}
+	Ok(keccak(&data))
+}
+
+/// Encode a struct type followed by its referenced struct types in alphabetical order.
+fn encode_type(name: &str, types: &Types) -> Result<String, String> {
+	let mut dependencies = BTreeSet::new();
+	collect_dependencies(name, types, &mut dependencies);
+	dependencies.remove(name);
+
+	let mut encoded = String::new();
+	for dependency in std::iter::once(name).chain(dependencies.iter().map(String::as_str)) {
+		let fields = types.get(dependency).ok_or_else(|| format!("unknown type {}", dependency))?;
+		let members = fields.iter()
+			.map(|field| format!("{} {}", field.type_, field.name))
+			.collect::<Vec<_>>();
+		encoded.push_str(&format!("{}({})", dependency, members.join(",")));
+	}
+	Ok(encoded)
+}
+
+fn collect_dependencies(name: &str, types: &Types, found: &mut BTreeSet<String>) {
+	let name = base_type(name);
+	if found.contains(name) {
+		return;
+	}
+	if let Some(fields) = types.get(name) {
+		found.insert(name.to_string());
+		for field in fields {
+			collect_dependencies(&field.type_, types, found);
+		}
+	}
+}
+
+/// Strip any array suffixes from a type name.
+fn base_type(name: &str) -> &str {
+	name.find('[').map(|index| &name[..index]).unwrap_or(name)
+}
+
+fn encode_value(type_: &str, value: &Value, types: &Types) -> Result<H256, String> {
+	if type_.ends_with(']') {
+		let element_type = &type_[..type_.rfind('[').unwrap_or_default()];
+		let elements = value.as_array().ok_or_else(|| format!("expected array for type {}", type_))?;
+		let mut data = Vec::with_capacity(elements.len() * 32);
+		for element in elements {
+			data.extend_from_slice(encode_value(element_type, element, types)?.as_bytes());
+		}
+		return Ok(keccak(&data));
+	}
+	if types.contains_key(type_) {
+		return hash_struct(type_, value, types);
+	}
+
+	match type_ {
+		"string" => {
+			let string = value.as_str().ok_or("expected string")?;
+			Ok(keccak(string.as_bytes()))
+		},
+		"bytes" => Ok(keccak(&parse_hex(value)?)),
+		"bool" => {
+			let boolean = value.as_bool().ok_or("expected bool")?;
+			Ok(H256::from_low_u64_be(boolean as u64))
+		},
+		"address" => {
+			let bytes = parse_hex(value)?;
+			if bytes.len() != 20 {
+				return Err("invalid address".to_string());
+			}
+			Ok(H160::from_slice(&bytes).into())
+		},
+		_ if type_.starts_with("bytes") => {
+			let bytes = parse_hex(value)?;
+			if bytes.len() > 32 {
+				return Err(format!("value too long for type {}", type_));
+			}
+			let mut word = H256::zero();
+			word.as_bytes_mut()[..bytes.len()].copy_from_slice(&bytes);
+			Ok(word)
+		},
+		_ if type_.starts_with("uint") || type_.starts_with("int") => {
+			let mut word = H256::zero();
+			parse_integer(value)?.to_big_endian(word.as_bytes_mut());
+			Ok(word)
+		},
+		_ => Err(format!("unsupported type {}", type_)),
+	}
+}
+
+fn parse_hex(value: &Value) -> Result<Vec<u8>, String> {
+	let string = value.as_str().ok_or("expected hex string")?;
+	if !string.starts_with("0x") {
+		return Err("hex string must start with 0x".to_string());
+	}
+	string[2..].from_hex().map_err(|_| "invalid hex string".to_string())
+}
+
+/// Parse a decimal or `0x` hex integer, given either as a JSON number or string.
+/// Negative values are returned in two's complement.
+fn parse_integer(value: &Value) -> Result<U256, String> {
+	let string = match value {
+		Value::Number(number) => number.to_string(),
+		Value::String(string) => string.clone(),
+		_ => return Err("expected integer".to_string()),
+	};
+	let (negative, digits) = if string.starts_with('-') {
+		(true, &string[1..])
+	} else {
+		(false, &string[..])
+	};
+	let integer = if digits.starts_with("0x") {
+		U256::from_str(&digits[2..]).ok()
+	} else {
+		U256::from_dec_str(digits).ok()
+	}.ok_or_else(|| format!("invalid integer {}", string))?;
+
+	if negative {
+		Ok((!integer).overflowing_add(U256::one()).0)
+	} else {
+		Ok(integer)
+	}
+}
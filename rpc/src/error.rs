@@ -0,0 +1,168 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Errors of the Ethereum RPC and the JSON-RPC errors they are reported as.
+
+use jsonrpc_core::{Error, ErrorCode, Value};
+use rustc_hex::ToHex;
+use frontier_rpc_primitives::ExecutionError;
+
+/// Failure of an Ethereum RPC method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthApiError {
+	/// The node failed to serve the request, e.g. to read its best block.
+	Internal(String),
+	/// A runtime API call failed. Reported with its own code, so clients can tell it from
+	/// a failure of the node itself.
+	RuntimeApi(String),
+	/// The requested block is not known to this node.
+	UnknownBlock,
+	/// The request parameters are invalid.
+	InvalidParams(String),
+	/// An execution reverted with the given output.
+	ExecutionReverted { data: Vec<u8> },
+	/// An execution ran out of gas.
+	OutOfGas,
+	/// An execution failed for any other reason.
+	ExecutionFailed,
+	/// The method is part of the Ethereum RPC but not supported by this node.
+	Unsupported(String),
+	/// A transaction is priced below the minimum gas price.
+	Underpriced,
+	/// The node is syncing and does not accept the request yet.
+	Syncing,
+	/// An execution did not finish before the deadline.
+	Timeout,
+	/// The node is serving as many expensive requests as it allows.
+	ServerBusy,
+	/// The requested blocks are older than the transaction statuses the runtime keeps.
+	Pruned,
+}
+
+impl From<ExecutionError> for EthApiError {
+	fn from(error: ExecutionError) -> Self {
+		match error {
+			ExecutionError::OutOfGas => EthApiError::OutOfGas,
+			ExecutionError::Reverted(data) => EthApiError::ExecutionReverted { data },
+			ExecutionError::Failed => EthApiError::ExecutionFailed,
+		}
+	}
+}
+
+impl From<EthApiError> for Error {
+	fn from(error: EthApiError) -> Self {
+		match error {
+			EthApiError::Internal(message) => Error {
+				code: ErrorCode::InternalError,
+				message,
+				data: None
+			},
+			EthApiError::RuntimeApi(message) => Error {
+				code: ErrorCode::ServerError(-32002),
+				message,
+				data: None
+			},
+			EthApiError::UnknownBlock => Error {
+				code: ErrorCode::ServerError(-32001),
+				message: "unknown block".to_string(),
+				data: None
+			},
+			EthApiError::InvalidParams(message) => Error {
+				code: ErrorCode::InvalidParams,
+				message,
+				data: None
+			},
+			EthApiError::ExecutionReverted { data } => Error {
+				code: ErrorCode::ServerError(3),
+				message: "execution reverted".to_string(),
+				data: Some(Value::String(format!("0x{}", data.to_hex::<String>())))
+			},
+			EthApiError::OutOfGas => Error {
+				code: ErrorCode::ServerError(-32000),
+				message: "out of gas".to_string(),
+				data: None
+			},
+			EthApiError::ExecutionFailed => Error {
+				code: ErrorCode::ServerError(-32015),
+				message: "execution failed".to_string(),
+				data: None
+			},
+			EthApiError::Unsupported(message) => Error {
+				code: ErrorCode::MethodNotFound,
+				message,
+				data: None
+			},
+			EthApiError::Underpriced => Error {
+				code: ErrorCode::ServerError(-32000),
+				message: "transaction underpriced".to_string(),
+				data: None
+			},
+			EthApiError::Syncing => Error {
+				code: ErrorCode::ServerError(-32000),
+				message: "node is syncing".to_string(),
+				data: None
+			},
+			EthApiError::Timeout => Error {
+				code: ErrorCode::ServerError(-32000),
+				message: "execution timed out".to_string(),
+				data: None
+			},
+			EthApiError::ServerBusy => Error {
+				code: ErrorCode::ServerError(-32005),
+				message: "server busy".to_string(),
+				data: None
+			},
+			EthApiError::Pruned => Error {
+				code: ErrorCode::ServerError(-32000),
+				message: "requested range is older than the pruning window".to_string(),
+				data: None
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reverts_should_be_distinguishable_from_node_failures() {
+		let reverted = Error::from(EthApiError::ExecutionReverted { data: vec![0x08, 0xc3] });
+		let failed = Error::from(EthApiError::RuntimeApi("fetch runtime block failed".to_string()));
+
+		assert_eq!(reverted.code, ErrorCode::ServerError(3));
+		assert_eq!(reverted.data, Some(Value::String("0x08c3".to_string())));
+		assert_eq!(failed.code, ErrorCode::ServerError(-32002));
+		assert_eq!(failed.message, "fetch runtime block failed");
+	}
+
+	#[test]
+	fn runtime_api_failures_should_be_distinguishable_from_internal_errors() {
+		let internal = Error::from(EthApiError::Internal("fetch best block failed".to_string()));
+		let runtime_api = Error::from(EthApiError::RuntimeApi("call runtime failed".to_string()));
+
+		assert_eq!(internal.code, ErrorCode::InternalError);
+		assert_ne!(internal.code, runtime_api.code);
+	}
+
+	#[test]
+	fn unknown_block_should_be_resource_not_found() {
+		let err = Error::from(EthApiError::UnknownBlock);
+
+		assert_eq!(err.code, ErrorCode::ServerError(-32001));
+		assert_eq!(err.message, "unknown block");
+	}
+}
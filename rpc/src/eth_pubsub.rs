@@ -0,0 +1,486 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum subscriptions, fed by the client's block import notifications.
+
+use std::{marker::PhantomData, sync::Arc};
+use ethereum::Block as EthereumBlock;
+use ethereum_types::H256;
+use jsonrpc_core::{Result, futures::{Future, Sink, Stream as _}};
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId, manager::SubscriptionManager};
+use futures::{StreamExt, TryStreamExt, future, stream::Stream};
+use log::warn;
+use sha3::{Keccak256, Digest};
+use sp_api::{ProvideRuntimeApi, BlockId};
+use sp_blockchain::{HashAndNumber, HeaderMetadata};
+use sp_runtime::traits::{Block as BlockT, Header as _, NumberFor, UniqueSaturatedInto};
+use sc_client_api::BlockchainEvents;
+use sc_rpc::Metadata;
+use frontier_rpc_core::EthPubSubApi as EthPubSubApiT;
+use frontier_rpc_core::types::{BlockTransactions, Filter, Header, Log, Rich, RichHeader};
+use frontier_rpc_core::types::pubsub::{Kind, Params, Result as PubSubResult};
+use frontier_rpc_primitives::{EthereumRuntimeApi, TransactionStatus};
+
+use crate::{
+	EthApiError, bloom_may_match, filter_matches, has_legacy_statuses, receipt_logs,
+	rich_block_build, transaction_status_at,
+};
+
+pub struct EthPubSubApi<B: BlockT, C> {
+	client: Arc<C>,
+	subscriptions: SubscriptionManager,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C> EthPubSubApi<B, C> {
+	pub fn new(client: Arc<C>, subscriptions: SubscriptionManager) -> Self {
+		Self { client, subscriptions, _marker: PhantomData }
+	}
+}
+
+/// Build the RPC header of an Ethereum block, with the fields `eth_getBlockBy*` returns.
+fn header_build(block: EthereumBlock) -> RichHeader {
+	let Rich { inner: block, extra_info } = rich_block_build(block, BlockTransactions::Hashes(Vec::new()));
+	Rich {
+		inner: Header {
+			hash: block.hash,
+			parent_hash: block.parent_hash,
+			uncles_hash: block.uncles_hash,
+			author: block.author,
+			miner: block.miner,
+			state_root: block.state_root,
+			transactions_root: block.transactions_root,
+			receipts_root: block.receipts_root,
+			number: block.number,
+			gas_used: block.gas_used,
+			gas_limit: block.gas_limit,
+			extra_data: block.extra_data,
+			logs_bloom: block.logs_bloom.unwrap_or_default(),
+			timestamp: block.timestamp,
+			difficulty: block.difficulty,
+			seal_fields: block.seal_fields,
+			size: block.size,
+		},
+		extra_info,
+	}
+}
+
+/// `newHeads` notifications for a stream of imported Ethereum blocks.
+fn new_heads(blocks: impl Stream<Item=EthereumBlock>) -> impl Stream<Item=PubSubResult> {
+	blocks.map(|block| PubSubResult::Header(Box::new(header_build(block))))
+}
+
+/// Logs of `block` matching `filter`, given the statuses of its transactions. Logs of
+/// blocks retracted from the best chain are flagged as `removed`.
+fn block_logs(
+	filter: &Filter,
+	block: &EthereumBlock,
+	statuses: &[TransactionStatus],
+	removed: bool,
+) -> Vec<Log> {
+	if !bloom_may_match(filter, &block.header.logs_bloom) {
+		return Vec::new();
+	}
+	let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+	statuses.iter()
+		.flat_map(|status| receipt_logs(block_hash, block.header.number, status))
+		.filter(|log| filter_matches(filter, log))
+		.map(|log| Log { removed, ..log })
+		.collect()
+}
+
+/// Ethereum block stored by the Substrate block `hash`, which has the same number.
+fn ethereum_block<B, C>(client: &C, hash: B::Hash, number: NumberFor<B>) -> Option<EthereumBlock> where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeApi<B>,
+{
+	client.runtime_api()
+		.block_by_number(&BlockId::Hash(hash), number.unique_saturated_into())
+		.map_err(|e| warn!("Failed to fetch the Ethereum block of {:?}: {:?}", hash, e))
+		.ok()
+		.flatten()
+}
+
+/// Statuses of the transactions of `block`, as stored by the Substrate block `hash`.
+fn transaction_statuses<B, C>(client: &C, hash: B::Hash, block: &EthereumBlock) -> Vec<TransactionStatus> where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeApi<B>,
+{
+	let at = BlockId::Hash(hash);
+	let legacy = match has_legacy_statuses(client, &at) {
+		Ok(legacy) => legacy,
+		Err(e) => {
+			warn!("Failed to fetch the runtime version of {:?}: {:?}", hash, e);
+			return Vec::new();
+		},
+	};
+	block.transactions.iter()
+		.filter_map(|transaction| {
+			let transaction_hash = H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice());
+			transaction_status_at(client, &at, transaction_hash, legacy)
+				.map_err(|e| warn!("Failed to fetch the status of transaction {:?}: {:?}", transaction_hash, e))
+				.ok()
+				.flatten()
+		})
+		.collect()
+}
+
+/// Blocks retracted from and enacted on the best chain when it moved from `old` to
+/// `new`. Without a previous best block, only `new` is enacted.
+fn best_chain_change<B, C>(
+	client: &C,
+	old: Option<B::Hash>,
+	new: HashAndNumber<B>,
+) -> (Vec<HashAndNumber<B>>, Vec<HashAndNumber<B>>) where
+	B: BlockT,
+	C: HeaderMetadata<B, Error=sp_blockchain::Error>,
+{
+	match old.and_then(|old| sp_blockchain::tree_route(client, old, new.hash).ok()) {
+		Some(route) => (route.retracted().to_vec(), route.enacted().to_vec()),
+		None => (Vec::new(), vec![new]),
+	}
+}
+
+impl<B, C> EthPubSubApiT for EthPubSubApi<B, C> where
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B> + BlockchainEvents<B> + Send + Sync + 'static,
+	C: HeaderMetadata<B, Error=sp_blockchain::Error>,
+	C::Api: EthereumRuntimeApi<B>,
+{
+	type Metadata = Metadata;
+
+	fn subscribe(
+		&self,
+		_: Self::Metadata,
+		subscriber: Subscriber<PubSubResult>,
+		kind: Kind,
+		params: Option<Params>,
+	) {
+		match kind {
+			Kind::NewHeads => {
+				let client = self.client.clone();
+				// The Ethereum block of a Substrate block is stored under the same number.
+				let blocks = self.client.import_notification_stream()
+					.filter(|notification| future::ready(notification.is_new_best))
+					.filter_map(move |notification| future::ready(
+						ethereum_block(&*client, notification.hash, *notification.header.number())
+					));
+
+				self.subscriptions.add(subscriber, |sink| {
+					new_heads(blocks)
+						.map(|result| Ok::<_, ()>(Ok(result)))
+						.compat()
+						.forward(sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)))
+						.map(|_| ())
+				});
+			},
+			Kind::Logs => {
+				let filter = match params {
+					Some(Params::Logs(filter)) => filter,
+					_ => Filter {
+						from_block: None,
+						to_block: None,
+						block_hash: None,
+						address: None,
+						topics: None,
+						limit: None,
+					},
+				};
+				let client = self.client.clone();
+				let mut best = None;
+				let logs = self.client.import_notification_stream()
+					.filter(|notification| future::ready(notification.is_new_best))
+					.map(move |notification| {
+						let (retracted, enacted) = best_chain_change(
+							&*client,
+							best.replace(notification.hash),
+							HashAndNumber { hash: notification.hash, number: *notification.header.number() },
+						);
+						let changes = retracted.into_iter().map(|block| (block, true))
+							.chain(enacted.into_iter().map(|block| (block, false)));
+						let mut logs = Vec::new();
+						for (HashAndNumber { hash, number }, removed) in changes {
+							if let Some(block) = ethereum_block(&*client, hash, number) {
+								let statuses = transaction_statuses(&*client, hash, &block);
+								logs.extend(block_logs(&filter, &block, &statuses, removed));
+							}
+						}
+						futures::stream::iter(logs)
+					})
+					.flatten()
+					.map(|log| PubSubResult::Log(Box::new(log)));
+
+				self.subscriptions.add(subscriber, |sink| {
+					logs
+						.map(|result| Ok::<_, ()>(Ok(result)))
+						.compat()
+						.forward(sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e)))
+						.map(|_| ())
+				});
+			},
+			_ => {
+				let _ = subscriber.reject(
+					EthApiError::Unsupported("subscription kind not supported".to_string()).into()
+				);
+			},
+		}
+	}
+
+	fn unsubscribe(&self, _: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum_types::{H160, H64, U256, Bloom, BloomInput};
+	use frontier_rpc_core::types::VariadicValue;
+	use futures::{compat::Future01CompatExt, executor::ThreadPool, FutureExt};
+	use jsonrpc_core::futures::future::{ExecuteError, Executor};
+	use crate::mock::{MockClient, MockState};
+
+	type Task = Box<dyn jsonrpc_core::futures::Future<Item=(), Error=()> + Send>;
+
+	/// Runs subscription tasks on a thread pool.
+	struct TestExecutor(ThreadPool);
+
+	impl Executor<Task> for TestExecutor {
+		fn execute(&self, task: Task) -> std::result::Result<(), ExecuteError<Task>> {
+			self.0.spawn_ok(task.compat().map(drop));
+			Ok(())
+		}
+	}
+
+	fn imported_block() -> EthereumBlock {
+		EthereumBlock {
+			header: ethereum::Header {
+				parent_hash: H256::repeat_byte(0xaa),
+				ommers_hash: H256::zero(),
+				beneficiary: H160::repeat_byte(0xbb),
+				state_root: H256::zero(),
+				transactions_root: H256::repeat_byte(0xdd),
+				receipts_root: H256::repeat_byte(0xee),
+				logs_bloom: Default::default(),
+				difficulty: U256::zero(),
+				number: U256::from(7),
+				gas_limit: U256::from(8_000_000),
+				gas_used: U256::zero(),
+				timestamp: 1_600_000_000,
+				extra_data: H256::zero(),
+				mix_hash: H256::zero(),
+				nonce: H64::zero(),
+			},
+			transactions: Vec::new(),
+			ommers: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn imported_block_should_be_notified_as_new_head() {
+		let block = imported_block();
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+
+		let notifications = futures::executor::block_on(
+			new_heads(futures::stream::iter(vec![block])).collect::<Vec<_>>()
+		);
+
+		assert_eq!(notifications.len(), 1);
+		match &notifications[0] {
+			PubSubResult::Header(header) => {
+				assert_eq!(header.inner.hash, Some(hash));
+				assert_eq!(header.inner.number, Some(U256::from(7)));
+				assert_eq!(header.inner.parent_hash, H256::repeat_byte(0xaa));
+				assert_eq!(header.inner.miner, H160::repeat_byte(0xbb));
+			},
+			_ => panic!("expected a header notification"),
+		}
+	}
+
+	fn status_with_log_from(address: H160, transaction_index: u32) -> TransactionStatus {
+		TransactionStatus {
+			transaction_hash: H256::repeat_byte(transaction_index as u8 + 1),
+			transaction_index,
+			from: H160::repeat_byte(0xaa),
+			to: Some(address),
+			contract_address: None,
+			gas_used: U256::from(21_000),
+			cumulative_gas_used: U256::from(21_000),
+			log_index: transaction_index,
+			logs: vec![ethereum::Log {
+				address,
+				topics: vec![H256::repeat_byte(0x01)],
+				data: Vec::new(),
+			}],
+			logs_bloom: Default::default(),
+		}
+	}
+
+	fn address_filter(address: H160) -> Filter {
+		Filter {
+			from_block: None,
+			to_block: None,
+			block_hash: None,
+			address: Some(VariadicValue::Single(address)),
+			topics: None,
+			limit: None,
+		}
+	}
+
+	#[test]
+	fn logs_of_the_subscribed_address_should_be_streamed() {
+		let watched = H160::repeat_byte(0x11);
+		let other = H160::repeat_byte(0x22);
+		let mut block = imported_block();
+		let mut bloom = Bloom::default();
+		bloom.accrue(BloomInput::Raw(&watched[..]));
+		bloom.accrue(BloomInput::Raw(&other[..]));
+		block.header.logs_bloom = bloom;
+		let statuses = vec![status_with_log_from(other, 0), status_with_log_from(watched, 1)];
+
+		let logs = block_logs(&address_filter(watched), &block, &statuses, false);
+
+		assert_eq!(logs.len(), 1);
+		assert_eq!(logs[0].address, watched);
+		assert_eq!(logs[0].transaction_hash, Some(H256::repeat_byte(0x02)));
+		assert_eq!(logs[0].block_number, Some(U256::from(7)));
+		assert!(!logs[0].removed);
+	}
+
+	#[test]
+	fn logs_of_retracted_blocks_should_be_removed() {
+		let watched = H160::repeat_byte(0x11);
+		let mut block = imported_block();
+		let mut bloom = Bloom::default();
+		bloom.accrue(BloomInput::Raw(&watched[..]));
+		block.header.logs_bloom = bloom;
+		let statuses = vec![status_with_log_from(watched, 0)];
+
+		let logs = block_logs(&address_filter(watched), &block, &statuses, true);
+
+		assert_eq!(logs.len(), 1);
+		assert!(logs[0].removed);
+	}
+
+	#[test]
+	fn blocks_ruled_out_by_their_bloom_should_have_no_logs() {
+		let watched = H160::repeat_byte(0x11);
+		let statuses = vec![status_with_log_from(watched, 0)];
+
+		let logs = block_logs(&address_filter(watched), &imported_block(), &statuses, false);
+
+		assert!(logs.is_empty());
+	}
+
+	#[test]
+	fn only_new_best_blocks_should_be_notified_as_new_heads() {
+		let block = |number: u64, parent_hash| EthereumBlock {
+			header: ethereum::Header {
+				number: U256::from(number),
+				parent_hash,
+				..imported_block().header
+			},
+			..imported_block()
+		};
+		let fork = block(1, H256::repeat_byte(0x01));
+		let best = block(1, H256::repeat_byte(0x02));
+		let client = MockClient::new(MockState::default());
+		let genesis = sp_consensus::SelectChain::best_chain(&client).unwrap().hash();
+		let pubsub = EthPubSubApi::new(
+			Arc::new(client.clone()),
+			SubscriptionManager::new(Arc::new(TestExecutor(ThreadPool::new().unwrap()))),
+		);
+		let (subscriber, id, notifications) = Subscriber::new_test("eth_subscription");
+		pubsub.subscribe(Default::default(), subscriber, Kind::NewHeads, None);
+		assert!(id.wait().unwrap().is_ok());
+
+		client.push_fork(genesis, MockState::default().with_block(fork, Vec::new()));
+		client.push_block(MockState::default().with_block(best.clone(), Vec::new()));
+
+		let notification = notifications.wait().next().unwrap().unwrap();
+		let notification: serde_json::Value = serde_json::from_str(&notification).unwrap();
+		let expected = serde_json::to_value(header_build(best.clone())).unwrap();
+		assert_eq!(notification["params"]["result"], expected);
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&best.header)).as_slice());
+		assert_eq!(expected["hash"], serde_json::json!(hash));
+		assert_eq!(expected["logsBloom"], serde_json::json!(best.header.logs_bloom));
+	}
+
+	#[test]
+	fn logs_subscription_should_stream_logs_and_remove_them_on_reorg() {
+		let watched = H160::repeat_byte(0x11);
+		let transaction = ethereum::Transaction {
+			nonce: U256::zero(),
+			gas_price: U256::one(),
+			gas_limit: U256::from(21_000),
+			action: ethereum::TransactionAction::Call(watched),
+			value: U256::zero(),
+			input: Vec::new(),
+			signature: ethereum::TransactionSignature::new(
+				27, H256::repeat_byte(0x01), H256::repeat_byte(0x01),
+			).unwrap(),
+		};
+		let transaction_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+		let mut bloom = Bloom::default();
+		bloom.accrue(BloomInput::Raw(&watched[..]));
+		let block = |parent_hash, transactions, logs_bloom| EthereumBlock {
+			header: ethereum::Header {
+				number: U256::one(),
+				parent_hash,
+				logs_bloom,
+				..imported_block().header
+			},
+			transactions,
+			ommers: Vec::new(),
+		};
+		let retracted = block(H256::repeat_byte(0x01), vec![transaction], bloom);
+		let enacted = block(H256::repeat_byte(0x02), Vec::new(), Bloom::default());
+		let status = TransactionStatus { transaction_hash, ..status_with_log_from(watched, 0) };
+		let client = MockClient::new(MockState::default());
+		let genesis = sp_consensus::SelectChain::best_chain(&client).unwrap().hash();
+		let pubsub = EthPubSubApi::new(
+			Arc::new(client.clone()),
+			SubscriptionManager::new(Arc::new(TestExecutor(ThreadPool::new().unwrap()))),
+		);
+		let (subscriber, id, notifications) = Subscriber::new_test("eth_subscription");
+		pubsub.subscribe(
+			Default::default(),
+			subscriber,
+			Kind::Logs,
+			Some(Params::Logs(address_filter(watched))),
+		);
+		assert!(id.wait().unwrap().is_ok());
+
+		client.push_block(MockState::default().with_block(retracted, vec![status]));
+		client.push_reorg(genesis, MockState::default().with_block(enacted, Vec::new()));
+
+		let logs = notifications.wait()
+			.take(2)
+			.map(|notification| {
+				let notification: serde_json::Value = serde_json::from_str(&notification.unwrap()).unwrap();
+				notification["params"]["result"].clone()
+			})
+			.collect::<Vec<_>>();
+		for log in &logs {
+			assert_eq!(log["address"], serde_json::json!(watched));
+			assert_eq!(log["transactionHash"], serde_json::json!(transaction_hash));
+		}
+		assert_eq!(logs[0]["removed"], serde_json::json!(false));
+		assert_eq!(logs[1]["removed"], serde_json::json!(true));
+	}
+}
@@ -14,66 +14,565 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{marker::PhantomData, sync::Arc};
-use std::collections::BTreeMap;
+use std::{marker::PhantomData, sync::{Arc, Mutex, mpsc, atomic::{AtomicUsize, Ordering}}, thread, time::Duration};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
 use ethereum::{Block as EthereumBlock, Transaction as EthereumTransaction};
-use ethereum_types::{H160, H256, H64, U256, U64};
-use jsonrpc_core::{BoxFuture, Result, ErrorCode, Error, futures::future::{self, Future}};
+use ethereum_types::{H160, H256, H64, U256, U64, Bloom, BloomInput};
+use jsonrpc_core::{BoxFuture, Result, Error, futures::future::{self, Future}};
 use futures::future::TryFutureExt;
 use sp_runtime::traits::{Block as BlockT, Header as _, UniqueSaturatedInto};
 use sp_runtime::transaction_validity::TransactionSource;
 use sp_api::{ProvideRuntimeApi, BlockId};
 use sp_consensus::SelectChain;
-use sp_transaction_pool::TransactionPool;
-use sc_client_api::backend::{StorageProvider, Backend, StateBackend};
+use sp_transaction_pool::{TransactionPool, InPoolTransaction, PoolStatus};
+use sc_transaction_graph::ChainApi;
+use sc_transaction_pool::BasicPool;
 use sha3::{Keccak256, Digest};
-use sp_runtime::traits::BlakeTwo256;
-use frontier_rpc_core::EthApi as EthApiT;
+use sc_keystore::KeyStorePtr;
+use frontier_rpc_core::{EthApi as EthApiT, NetApi as NetApiT, TxPoolApi as TxPoolApiT};
 use frontier_rpc_core::types::{
-	BlockNumber, Bytes, CallRequest, EthAccount, Filter, Index, Log, Receipt, RichBlock,
-	SyncStatus, Transaction, Work, Rich, Block, BlockTransactions
+	BlockNumber, Bytes, CallRequest, EthAccount, FeeHistory, Filter, Index, Log, Receipt, RichBlock,
+	SyncInfo, SyncStatus, Transaction, Work, Rich, Block, BlockTransactions, TransactionMap,
+	TxPoolResult, VariadicValue,
 };
-use frontier_rpc_primitives::{EthereumRuntimeApi, ConvertTransaction, TransactionStatus};
-
-pub use frontier_rpc_core::EthApiServer;
+use frontier_rpc_primitives::{
+	EthereumRuntimeApi, ConvertTransaction, TransactionStatus, LegacyTransactionStatus,
+	ExecutionError, MAX_BLOCKS_IN_RANGE, recover_signer,
+};
+use once_cell::sync::OnceCell;
+
+mod batch;
+mod eip712;
+mod error;
+mod eth_pubsub;
+#[cfg(test)]
+mod mock;
+mod signing;
+
+pub use frontier_rpc_core::{
+	EthApiServer, EthPubSubApiServer, EthSigningApiServer, NetApiServer, TxPoolApiServer,
+};
+pub use batch::{BatchSizeLimit, DEFAULT_MAX_BATCH_SIZE};
+pub use error::EthApiError;
+pub use eth_pubsub::EthPubSubApi;
+pub use signing::{EthSigningApi, ETH_KEY_TYPE, keystore_addresses};
+
+/// Methods which this node does not support and which only ever return errors or
+/// placeholder values.
+pub const UNSUPPORTED_METHODS: &[&str] = &[
+	"eth_hashrate",
+	"eth_getWork",
+	"eth_submitWork",
+	"eth_submitHashrate",
+	"eth_getCompilers",
+	"eth_compileLLL",
+	"eth_compileSolidity",
+	"eth_compileSerpent",
+	"eth_getProof",
+	"eth_sendTransaction",
+	"eth_signTransaction",
+];
+
+/// Drop all `UNSUPPORTED_METHODS` from a set of RPC methods, so that they are not
+/// registered with the server at all.
+pub fn without_unsupported_methods<T>(
+	methods: impl IntoIterator<Item=(String, T)>
+) -> Vec<(String, T)> {
+	methods
+		.into_iter()
+		.filter(|(name, _)| !UNSUPPORTED_METHODS.contains(&name.as_str()))
+		.collect()
+}
 
 fn internal_err(message: &str) -> Error {
-	Error {
-		code: ErrorCode::InternalError,
-		message: message.to_string(),
-		data: None
+	EthApiError::Internal(message.to_string()).into()
+}
+
+fn runtime_api_err(message: &str) -> Error {
+	EthApiError::RuntimeApi(message.to_string()).into()
+}
+
+/// Default maximum size of contract creation code accepted by `eth_call` and
+/// `eth_estimateGas`, as defined by EIP-3860.
+pub const MAX_INIT_CODE_SIZE: usize = 2 * 24576;
+
+/// Maximum number of transactions returned by `txpool_content`.
+pub const MAX_TXPOOL_CONTENT: usize = 4096;
+
+/// Maximum number of blocks a single block range query may span.
+pub const MAX_BLOCK_RANGE: u64 = 1024;
+
+fn invalid_params_err(message: &str) -> Error {
+	EthApiError::InvalidParams(message.to_string()).into()
+}
+
+fn mining_not_supported_err() -> Error {
+	EthApiError::Unsupported("mining not supported".to_string()).into()
+}
+
+fn method_not_supported_err() -> Error {
+	EthApiError::Unsupported("method not supported".to_string()).into()
+}
+
+/// Validate a `from..=to` block range: it must not be reversed, must end at or
+/// before the `best` block and may span at most `max` blocks.
+pub fn validate_range(from: u64, to: u64, best: u64, max: u64) -> Result<(u64, u64)> {
+	if from > to {
+		return Err(invalid_params_err("invalid block range: from block is after to block"));
+	}
+	if to > best {
+		return Err(invalid_params_err("invalid block range: to block is beyond the best block"));
+	}
+	if to - from >= max {
+		return Err(invalid_params_err(
+			&format!("invalid block range: range exceeds the maximum of {} blocks", max)
+		));
+	}
+	Ok((from, to))
+}
+
+/// Decode a raw signed transaction, returning it with its Ethereum hash.
+fn decode_raw_transaction(bytes: &[u8]) -> Result<(ethereum::Transaction, H256)> {
+	let transaction = rlp::decode::<ethereum::Transaction>(bytes)
+		.map_err(|_| invalid_params_err("decode transaction failed"))?;
+	let transaction_hash = H256::from_slice(
+		Keccak256::digest(&rlp::encode(&transaction)).as_slice()
+	);
+	Ok((transaction, transaction_hash))
+}
+
+/// Reject contract creation requests whose init code exceeds `max` bytes.
+fn check_init_code_size(request: &CallRequest, max: usize) -> Result<()> {
+	let size = request.data.as_ref().map(|data| data.0.len()).unwrap_or_default();
+	if request.to.is_none() && size > max {
+		return Err(invalid_params_err(
+			&format!("max initcode size exceeded: {} > {}", size, max)
+		));
+	}
+	Ok(())
+}
+
+/// Reject transactions priced below both the chain minimum and the node's own floor.
+fn check_gas_price(gas_price: U256, chain_min_gas_price: U256, node_min_gas_price: U256) -> Result<()> {
+	if gas_price < chain_min_gas_price.max(node_min_gas_price) {
+		return Err(EthApiError::Underpriced.into());
+	}
+	Ok(())
+}
+
+/// Deadline of runtime executions, and the threads they run on.
+struct ExecutionTimeout {
+	timeout: Duration,
+	threads: Arc<RequestLimiter>,
+}
+
+impl ExecutionTimeout {
+	fn new(timeout: Duration, max_threads: usize) -> Self {
+		Self { timeout, threads: Arc::new(RequestLimiter::new(max_threads)) }
+	}
+}
+
+/// Run `f` on its own thread and give up once the timeout has passed. Runtime calls execute
+/// on a throwaway overlay, so an abandoned execution cannot affect any state; it keeps its
+/// thread until it completes or runs out of gas. Executions are rejected while all threads
+/// are taken, so abandoned ones cannot pile up.
+fn with_timeout<T, F>(timeout: Option<&ExecutionTimeout>, f: F) -> Result<T> where
+	T: Send + 'static,
+	F: FnOnce() -> Result<T> + Send + 'static,
+{
+	let timeout = match timeout {
+		Some(timeout) => timeout,
+		None => return f(),
+	};
+
+	let thread_permit = RequestLimiter::try_acquire(&timeout.threads)?;
+	let (sender, receiver) = mpsc::channel();
+	thread::Builder::new()
+		.name("eth-call".to_string())
+		.spawn(move || {
+			let result = f();
+			drop(thread_permit);
+			let _ = sender.send(result);
+		})
+		.map_err(|_| internal_err("spawn execution thread failed"))?;
+
+	match receiver.recv_timeout(timeout.timeout) {
+		Ok(result) => result,
+		Err(mpsc::RecvTimeoutError::Timeout) => Err(EthApiError::Timeout.into()),
+		Err(mpsc::RecvTimeoutError::Disconnected) => Err(internal_err("execution thread panicked")),
+	}
+}
+
+/// Bounds how many expensive requests run at once. Requests beyond the limit fail fast
+/// rather than queueing behind executions that may hold worker threads and EVM memory.
+pub struct RequestLimiter {
+	limit: usize,
+	active: AtomicUsize,
+}
+
+/// Slot held by a running request, released when dropped.
+struct RequestPermit(Arc<RequestLimiter>);
+
+impl RequestLimiter {
+	pub fn new(limit: usize) -> Self {
+		Self { limit, active: AtomicUsize::new(0) }
+	}
+
+	fn try_acquire(limiter: &Arc<Self>) -> Result<RequestPermit> {
+		let mut active = limiter.active.load(Ordering::Acquire);
+		loop {
+			if active >= limiter.limit {
+				return Err(EthApiError::ServerBusy.into());
+			}
+			match limiter.active.compare_exchange(active, active + 1, Ordering::AcqRel, Ordering::Acquire) {
+				Ok(_) => return Ok(RequestPermit(limiter.clone())),
+				Err(current) => active = current,
+			}
+		}
+	}
+}
+
+impl Drop for RequestPermit {
+	fn drop(&mut self) {
+		self.0.active.fetch_sub(1, Ordering::AcqRel);
+	}
+}
+
+/// Senders of the pooled Ethereum transactions seen by the last lookup, by transaction
+/// hash, so that repeated pending nonce lookups recover each sender once.
+#[derive(Default)]
+struct SenderCache(Mutex<HashMap<H256, Option<H160>>>);
+
+impl SenderCache {
+	/// Senders of `transactions`, recovering only those the last lookup did not see.
+	/// Transactions that left the pool since then are forgotten.
+	fn senders(&self, transactions: &[EthereumTransaction]) -> Vec<Option<H160>> {
+		let mut cache = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		let mut seen = HashMap::with_capacity(transactions.len());
+		let senders = transactions.iter()
+			.map(|transaction| {
+				let hash = H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice());
+				let sender = cache.remove(&hash).unwrap_or_else(|| {
+					recover_signer(transaction).map(|(signer, _)| signer)
+				});
+				seen.insert(hash, sender);
+				sender
+			})
+			.collect();
+		*cache = seen;
+		senders
+	}
+}
+
+/// Reports whether the node is still importing blocks to catch up with the network.
+pub trait SyncStatusProvider: Send + Sync {
+	fn is_major_syncing(&self) -> bool;
+
+	/// Block the node was at when the current sync started and the highest block seen
+	/// from peers, if known.
+	fn sync_range(&self) -> Option<(u64, u64)> {
+		None
+	}
+}
+
+/// Lists the transactions of a pool that wait for an earlier one, which
+/// `TransactionPool` does not expose.
+pub trait FutureTransactions<B: BlockT> {
+	fn future_transactions(&self) -> Vec<B::Extrinsic>;
+}
+
+impl<PoolApi, B> FutureTransactions<B> for BasicPool<PoolApi, B> where
+	B: BlockT,
+	PoolApi: ChainApi<Block=B, Hash=B::Hash> + 'static,
+{
+	fn future_transactions(&self) -> Vec<B::Extrinsic> {
+		self.pool()
+			.validated_pool()
+			.futures()
+			.into_iter()
+			.map(|(_, xt)| xt)
+			.collect()
+	}
+}
+
+/// Reports the connectivity of the node's network service.
+pub trait NetworkStatusProvider: Send + Sync {
+	/// Number of peers the node is connected to.
+	fn peer_count(&self) -> usize;
+
+	/// Whether the node accepts incoming connections.
+	fn is_listening(&self) -> bool;
+}
+
+/// Peer count reported by `net_peerCount`, as a hex quantity. Nodes without a network
+/// have no peers.
+fn peer_count_build(network: Option<&dyn NetworkStatusProvider>) -> String {
+	format!("0x{:x}", network.map_or(0, |network| network.peer_count()))
+}
+
+/// Sync status reported by `eth_syncing` for a node whose best block is `current`.
+fn sync_status_build(provider: &dyn SyncStatusProvider, current: u64) -> SyncStatus {
+	if !provider.is_major_syncing() {
+		return SyncStatus::None;
+	}
+	let (starting, highest) = provider.sync_range().unwrap_or((current, current));
+	SyncStatus::Info(SyncInfo {
+		starting_block: U256::from(starting),
+		current_block: U256::from(current),
+		highest_block: U256::from(highest.max(current)),
+		warp_chunks_amount: None,
+		warp_chunks_processed: None,
+	})
+}
+
+/// Checks applied by `eth_sendRawTransaction` before a transaction reaches the pool.
+#[derive(Clone, Debug, Default)]
+pub struct SubmissionConfig {
+	/// Floor enforced by this node on top of the chain's minimum gas price.
+	pub min_gas_price: U256,
+	/// Whether to accept transactions while the node is still syncing.
+	pub accept_while_syncing: bool,
+}
+
+/// Reject transactions while syncing, as they would be checked against stale state.
+fn check_not_syncing(sync_status: &dyn SyncStatusProvider, config: &SubmissionConfig) -> Result<()> {
+	if !config.accept_while_syncing && sync_status.is_major_syncing() {
+		return Err(EthApiError::Syncing.into());
+	}
+	Ok(())
+}
+
+/// Reject queries starting at `from` when the runtime, keeping the transaction statuses of
+/// the last `retention` blocks, has already pruned those of `from`, rather than returning
+/// partial results.
+fn check_retention_window(from: u64, best: u64, retention: u64) -> Result<()> {
+	if best > retention && from <= best - retention {
+		return Err(EthApiError::Pruned.into());
+	}
+	Ok(())
+}
+
+/// Native number of the block `number` refers to, seen from the best block `best`. Block
+/// hashes are resolved by `number_of_hash`, which must read the same view of the chain.
+fn resolve_block_number<F>(number: Option<BlockNumber>, best: u32, number_of_hash: F) -> Option<u32> where
+	F: FnOnce(H256) -> Option<u32>,
+{
+	match number.unwrap_or(BlockNumber::Latest) {
+		BlockNumber::Hash { hash, .. } => number_of_hash(hash),
+		number @ BlockNumber::Num(_) => number.to_min_block_num()
+			.map(|number| number.unique_saturated_into()),
+		BlockNumber::Latest => Some(best),
+		BlockNumber::Earliest => Some(1),
+		BlockNumber::Pending => None,
+	}
+}
+
+/// Version of `EthereumRuntimeApi` in which a runtime method became available.
+fn required_runtime_api_version(method: &str) -> u32 {
+	match method {
+		"account_code_len" | "block_transaction_hashes" | "extrinsic_filter" => 2,
+		"blocks_in_range" => 3,
+		"substrate_account" => 4,
+		"create" => 6,
+		"transaction_status_retention" => 7,
+		_ => 1,
 	}
 }
 
-pub struct EthApi<B: BlockT, C, SC, P, CT, BE> {
+fn unsupported_runtime_method_err(method: &str) -> Error {
+	EthApiError::Unsupported(format!("method {} not supported by runtime", method)).into()
+}
+
+/// Check whether a runtime implementing `version` of `EthereumRuntimeApi` provides `method`.
+fn check_runtime_method(version: u32, method: &str) -> Result<()> {
+	if version >= required_runtime_api_version(method) {
+		Ok(())
+	} else {
+		Err(unsupported_runtime_method_err(method))
+	}
+}
+
+/// Whether the runtime at `at` returns transaction statuses in the layout used before
+/// version 5 of `EthereumRuntimeApi`.
+pub(crate) fn has_legacy_statuses<B, C>(client: &C, at: &BlockId<B>) -> Result<bool> where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeApi<B>,
+{
+	client.runtime_api()
+		.has_api_with::<dyn EthereumRuntimeApi<B>, _>(at, |version| version < 5)
+		.map_err(|_| runtime_api_err("fetch runtime api version failed"))
+}
+
+/// Status of the transaction `hash` as stored at `at`. Legacy statuses carry no gas usage
+/// nor log positions, which are then reported as zero.
+pub(crate) fn transaction_status_at<B, C>(
+	client: &C,
+	at: &BlockId<B>,
+	hash: H256,
+	legacy: bool,
+) -> Result<Option<TransactionStatus>> where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeApi<B>,
+{
+	let api = client.runtime_api();
+	#[allow(deprecated)]
+	let status = if legacy {
+		api.transaction_status_before_version_5(at, hash).map(|status| status.map(Into::into))
+	} else {
+		api.transaction_status(at, hash)
+	};
+	status.map_err(|_| runtime_api_err("fetch runtime transaction status failed"))
+}
+
+/// Convert the status of a transaction found by a runtime before version 5.
+fn upgrade_status<T, U>(
+	found: Option<(T, U, LegacyTransactionStatus)>,
+) -> Option<(T, U, TransactionStatus)> {
+	found.map(|(transaction, block, status)| (transaction, block, status.into()))
+}
+
+/// Pad the gas a dry run used by `percent`, so that an estimate stays sufficient when
+/// state changes slightly before the transaction is included.
+fn apply_gas_margin(used_gas: U256, percent: u32) -> U256 {
+	let margin = used_gas.saturating_mul(U256::from(percent)) / U256::from(100);
+	used_gas.saturating_add(margin)
+}
+
+fn execution_err(error: ExecutionError) -> Error {
+	EthApiError::from(error).into()
+}
+
+pub struct EthApi<B: BlockT, C, SC, P, CT> {
 	pool: Arc<P>,
 	client: Arc<C>,
 	select_chain: SC,
 	convert_transaction: CT,
 	is_authority: bool,
-	_marker: PhantomData<(B,BE)>,
+	sync_status: Arc<dyn SyncStatusProvider>,
+	submission: SubmissionConfig,
+	execution_timeout: Option<ExecutionTimeout>,
+	gas_estimate_margin: u32,
+	max_init_code_size: usize,
+	max_log_block_range: u64,
+	archive: bool,
+	chain_id: OnceCell<u64>,
+	request_limiter: Option<Arc<RequestLimiter>>,
+	keystore: Option<KeyStorePtr>,
+	network: Option<Arc<dyn NetworkStatusProvider>>,
+	senders: SenderCache,
+	_marker: PhantomData<B>,
 }
 
-impl<B: BlockT, C, SC, P, CT, BE> EthApi<B, C, SC, P, CT, BE> {
+impl<B: BlockT, C, SC, P, CT> EthApi<B, C, SC, P, CT> {
 	pub fn new(
 		client: Arc<C>,
 		select_chain: SC,
 		pool: Arc<P>,
 		convert_transaction: CT,
-		is_authority: bool
+		is_authority: bool,
+		sync_status: Arc<dyn SyncStatusProvider>,
+		submission: SubmissionConfig,
 	) -> Self {
-		Self { client, select_chain, pool, convert_transaction, is_authority, _marker: PhantomData }
+		Self {
+			client,
+			select_chain,
+			pool,
+			convert_transaction,
+			is_authority,
+			sync_status,
+			submission,
+			execution_timeout: None,
+			gas_estimate_margin: 0,
+			max_init_code_size: MAX_INIT_CODE_SIZE,
+			max_log_block_range: MAX_BLOCK_RANGE,
+			archive: false,
+			chain_id: OnceCell::new(),
+			request_limiter: None,
+			keystore: None,
+			network: None,
+			senders: SenderCache::default(),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Abort `eth_call` and `eth_estimateGas` executions running longer than `timeout`.
+	/// Executions run on at most `max_threads` threads, which aborted executions keep
+	/// until they complete.
+	pub fn with_execution_timeout(mut self, timeout: Duration, max_threads: usize) -> Self {
+		self.execution_timeout = Some(ExecutionTimeout::new(timeout, max_threads));
+		self
+	}
+
+	/// Add `percent` of the gas used by the dry run to `eth_estimateGas` results.
+	pub fn with_gas_estimate_margin(mut self, percent: u32) -> Self {
+		self.gas_estimate_margin = percent;
+		self
+	}
+
+	/// Reject `eth_call` and `eth_estimateGas` contract creations whose init code exceeds
+	/// `max` bytes.
+	pub fn with_max_init_code_size(mut self, max: usize) -> Self {
+		self.max_init_code_size = max;
+		self
+	}
+
+	/// Reject `eth_getLogs` requests spanning more than `max` blocks.
+	pub fn with_max_log_block_range(mut self, max: u64) -> Self {
+		self.max_log_block_range = max;
+		self
+	}
+
+	/// Reject `eth_call`, `eth_estimateGas` and `eth_getLogs` requests with a "server busy"
+	/// error while `limit` of them are already running.
+	pub fn with_request_limit(mut self, limit: usize) -> Self {
+		self.request_limiter = Some(Arc::new(RequestLimiter::new(limit)));
+		self
+	}
+
+	/// Report the Ethereum accounts held in `keystore` from `eth_accounts`.
+	pub fn with_keystore(mut self, keystore: KeyStorePtr) -> Self {
+		self.keystore = Some(keystore);
+		self
+	}
+
+	/// Report peers and listening state of `network` from the `net_*` methods.
+	pub fn with_network(mut self, network: Arc<dyn NetworkStatusProvider>) -> Self {
+		self.network = Some(network);
+		self
+	}
+
+	/// Take a slot for an expensive request, if requests are limited.
+	fn acquire_request_permit(&self) -> Result<Option<RequestPermit>> {
+		self.request_limiter.as_ref()
+			.map(RequestLimiter::try_acquire)
+			.transpose()
+	}
+
+	/// Read the transaction statuses of a block from the state of that block, which
+	/// archive nodes keep after the runtime pruned the statuses from later states. Log
+	/// queries are then not bound by the runtime's retention.
+	pub fn with_archive_state(mut self) -> Self {
+		self.archive = true;
+		self
 	}
 }
 
-fn rich_block_build(block: ethereum::Block) -> RichBlock {
+/// Build an RPC block. Every field but `transactions` depends on the block alone, so the
+/// full and summary forms of a block only differ there.
+fn rich_block_build(block: ethereum::Block, transactions: BlockTransactions) -> RichBlock {
+	let size = U256::from(rlp::encode(&block).len());
 	Rich {
 		inner: Block {
-			hash: None, // TODO
+			hash: Some(H256::from_slice(
+				Keccak256::digest(&rlp::encode(&block.header)).as_slice()
+			)),
 			parent_hash: block.header.parent_hash,
 			uncles_hash: H256::zero(), // TODO
-			author: H160::default(), // TODO
-			miner: H160::default(), // TODO
+			author: block.header.beneficiary,
+			miner: block.header.beneficiary,
 			state_root: block.header.state_root,
 			transactions_root: block.header.transactions_root,
 			receipts_root: block.header.receipts_root,
@@ -87,41 +586,63 @@ fn rich_block_build(block: ethereum::Block) -> RichBlock {
 			total_difficulty: None, // TODO
 			seal_fields: vec![], // TODO
 			uncles: vec![], // TODO
-			// TODO expected struct `frontier_rpc_core::types::transaction::Transaction`,
-			// found struct `ethereum::transaction::Transaction`
-			transactions: BlockTransactions::Full(vec![]),
-			size: None // TODO
+			transactions,
+			size: Some(size),
 		},
 		extra_info: BTreeMap::new()
 	}
 }
 
+/// Build the RPC transactions of a block, given the status of each of them.
+fn full_transactions_build(
+	block: &EthereumBlock,
+	statuses: Vec<Option<TransactionStatus>>,
+) -> Vec<Transaction> {
+	block.transactions.iter().cloned()
+		.zip(statuses)
+		.map(|(transaction, status)| transaction_build(transaction, Some(block.clone()), status))
+		.collect()
+}
+
+/// Build an RPC transaction. Block and status are `None` for transactions which
+/// are not part of a block yet, leaving the corresponding fields empty.
 fn transaction_build(
 	transaction: EthereumTransaction,
-	block: EthereumBlock,
-	status: TransactionStatus
+	block: Option<EthereumBlock>,
+	status: Option<TransactionStatus>
 ) -> Transaction {
+	let (from, to) = match status.as_ref() {
+		Some(status) => (status.from, status.to),
+		None => (
+			recover_signer(&transaction).map(|(signer, _)| signer).unwrap_or_default(),
+			match transaction.action {
+				ethereum::TransactionAction::Call(to) => Some(to),
+				ethereum::TransactionAction::Create => None,
+			},
+		),
+	};
+
 	Transaction {
 		hash: H256::from_slice(
 			Keccak256::digest(&rlp::encode(&transaction)).as_slice()
 		),
 		nonce: transaction.nonce,
-		block_hash: Some(H256::from_slice(
+		block_hash: block.as_ref().map(|block| H256::from_slice(
 			Keccak256::digest(&rlp::encode(&block.header)).as_slice()
 		)),
-		block_number: Some(block.header.number),
-		transaction_index: Some(U256::from(
+		block_number: block.as_ref().map(|block| block.header.number),
+		transaction_index: status.as_ref().map(|status| U256::from(
 			UniqueSaturatedInto::<u32>::unique_saturated_into(
 				status.transaction_index
 			)
 		)),
-		from: status.from,
-		to: status.to,
+		from,
+		to,
 		value: transaction.value,
 		gas_price: transaction.gas_price,
 		gas: transaction.gas_limit,
 		input: Bytes(transaction.input),
-		creates: status.contract_address,
+		creates: status.and_then(|status| status.contract_address),
 		raw: Bytes(vec![]), // TODO
 		public_key: None, // TODO
 		chain_id: None, // TODO
@@ -133,66 +654,500 @@ fn transaction_build(
 	}
 }
 
-impl<B, C, SC, P, CT, BE> EthApi<B, C, SC, P, CT, BE> where
-	C: ProvideRuntimeApi<B> + StorageProvider<B,BE>,
+/// Build the RPC logs of a mined transaction, numbered both within its block and within
+/// the transaction itself.
+fn receipt_logs(block_hash: H256, block_number: U256, status: &TransactionStatus) -> Vec<Log> {
+	status.logs.iter().enumerate().map(|(index, log)| Log {
+		address: log.address,
+		topics: log.topics.clone(),
+		data: Bytes(log.data.clone()),
+		block_hash: Some(block_hash),
+		block_number: Some(block_number),
+		transaction_hash: Some(status.transaction_hash),
+		transaction_index: Some(status.transaction_index.into()),
+		log_index: Some(U256::from(status.log_index) + U256::from(index)),
+		transaction_log_index: Some(U256::from(index)),
+		log_type: "mined".to_string(),
+		removed: false,
+	}).collect()
+}
+
+/// Whether `log` matches the address and topics of `filter`. Topics are positional:
+/// every position has to match, and each position matches any of its values.
+fn filter_matches(filter: &Filter, log: &Log) -> bool {
+	filter.address.as_ref().map_or(true, |address| address.matches(Some(&log.address))) &&
+		filter.topics.as_ref().map_or(true, |topics| {
+			topics.iter().enumerate().all(|(position, topic)| topic.matches(log.topics.get(position)))
+		})
+}
+
+/// Build the fee history of consecutive `blocks` starting at `oldest`. Without EIP-1559,
+/// the minimum `gas_price` stands in for the base fee and no priority fee is paid, so
+/// every requested reward percentile is zero.
+fn fee_history_build(
+	oldest: u32,
+	blocks: &[EthereumBlock],
+	gas_price: U256,
+	reward_percentiles: Option<&[f64]>,
+) -> FeeHistory {
+	FeeHistory {
+		oldest_block: U256::from(oldest),
+		base_fee_per_gas: vec![gas_price; blocks.len() + 1],
+		gas_used_ratio: blocks.iter()
+			.map(|block| {
+				if block.header.gas_limit.is_zero() {
+					0.0
+				} else {
+					block.header.gas_used.low_u64() as f64 / block.header.gas_limit.low_u64() as f64
+				}
+			})
+			.collect(),
+		reward: reward_percentiles.map(|percentiles| {
+			blocks.iter().map(|_| vec![U256::zero(); percentiles.len()]).collect()
+		}),
+	}
+}
+
+/// Next nonce of an account whose on-chain nonce is `on_chain`, counting the pooled
+/// transactions that directly follow it. Nonces after a gap cannot be mined yet.
+fn pending_nonce<I: IntoIterator<Item = U256>>(on_chain: U256, pooled: I) -> U256 {
+	let pooled = pooled.into_iter().collect::<std::collections::BTreeSet<_>>();
+	let mut nonce = on_chain;
+	while pooled.contains(&nonce) {
+		nonce = nonce.saturating_add(U256::one());
+	}
+	nonce
+}
+
+/// Whether a block with `bloom` may contain logs matching `filter`. Blooms have false
+/// positives only, so a `false` lets the block be skipped without reading its logs.
+fn bloom_may_match(filter: &Filter, bloom: &Bloom) -> bool {
+	fn any_in_bloom<T: AsRef<[u8]>>(bloom: &Bloom, values: &[T]) -> bool {
+		values.is_empty() ||
+			values.iter().any(|value| bloom.contains_input(BloomInput::Raw(value.as_ref())))
+	}
+	let address_matches = match &filter.address {
+		Some(VariadicValue::Single(address)) => any_in_bloom(bloom, &[*address]),
+		Some(VariadicValue::Multiple(addresses)) => any_in_bloom(bloom, addresses),
+		_ => true,
+	};
+	address_matches && filter.topics.as_ref().map_or(true, |topics| {
+		topics.iter().all(|topic| match topic {
+			VariadicValue::Single(topic) => any_in_bloom(bloom, &[*topic]),
+			VariadicValue::Multiple(topics) => any_in_bloom(bloom, topics),
+			VariadicValue::Null => true,
+		})
+	})
+}
+
+/// Ready transactions are pending, future-nonce ones are queued.
+fn txpool_status(status: PoolStatus) -> TxPoolResult<U256> {
+	TxPoolResult {
+		pending: U256::from(status.ready),
+		queued: U256::from(status.future),
+	}
+}
+
+/// Build the RPC form of transactions that are not mined yet.
+fn pending_transactions_build(transactions: Vec<EthereumTransaction>) -> Vec<Transaction> {
+	transactions.into_iter()
+		.map(|transaction| transaction_build(transaction, None, None))
+		.collect()
+}
+
+/// Group transactions by sender and nonce.
+fn transaction_map(transactions: Vec<Transaction>) -> TransactionMap {
+	let mut map = TransactionMap::new();
+	for transaction in transactions {
+		map.entry(transaction.from)
+			.or_default()
+			.insert(transaction.nonce, transaction);
+	}
+	map
+}
+
+impl<B, C, SC, P, CT> EthApi<B, C, SC, P, CT> where
+	C: ProvideRuntimeApi<B>,
 	C::Api: EthereumRuntimeApi<B>,
-	BE: Backend<B> + 'static,
-	BE::State: StateBackend<BlakeTwo256>,
 	B: BlockT<Hash=H256> + Send + Sync + 'static,
 	C: Send + Sync + 'static,
 	SC: SelectChain<B> + Clone + 'static,
 	P: TransactionPool<Block=B> + Send + Sync + 'static,
 	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
 {
-	fn native_block_number(&self, number: Option<BlockNumber>) -> Result<Option<u32>> {
-		let header = self
-			.select_chain
-			.best_chain()
+	/// Chain id of the runtime, fetched on first use only. Changing it would require a
+	/// runtime upgrade that breaks every signed transaction anyway.
+	fn cached_chain_id(&self) -> Result<u64> {
+		self.chain_id.get_or_try_init(|| {
+			let header = self.select_chain.best_chain()
+				.map_err(|_| internal_err("fetch header failed"))?;
+			self.client.runtime_api().chain_id(&BlockId::Hash(header.hash()))
+				.map_err(|_| runtime_api_err("fetch runtime chain id failed"))
+		}).map(|chain_id| *chain_id)
+	}
+
+	/// Resolve `number` against the given best block, so that every lookup made by a
+	/// method shares one view of the chain even if the best block changes meanwhile.
+	fn native_block_number_at(&self, best: &B::Header, number: Option<BlockNumber>) -> Option<u32> {
+		resolve_block_number(number, best.number().clone().unique_saturated_into(), |hash| {
+			self.client.runtime_api()
+				.block_by_hash(&BlockId::Hash(best.hash()), hash)
+				.ok()
+				.and_then(|block| block)
+				.map(|block| block.header.number.as_u32())
+		})
+	}
+
+	/// Ethereum transactions among the first `limit` ready transactions of the pool, as
+	/// extracted by the runtime at `id`.
+	fn ready_transactions(&self, id: &BlockId<B>, limit: usize) -> Result<Vec<EthereumTransaction>> {
+		let xts = self.pool
+			.ready()
+			.take(limit)
+			.map(|in_pool| in_pool.data().clone())
+			.collect::<Vec<_>>();
+		self.ethereum_transactions(id, xts)
+	}
+
+	/// Ethereum transactions carried by `xts`, as extracted by the runtime at `id`.
+	fn ethereum_transactions(
+		&self,
+		id: &BlockId<B>,
+		xts: Vec<B::Extrinsic>,
+	) -> Result<Vec<EthereumTransaction>> {
+		self.ensure_runtime_method(id, "extrinsic_filter")?;
+		self.client.runtime_api()
+			.extrinsic_filter(id, xts)
+			.map_err(|_| runtime_api_err("fetch runtime extrinsic filter failed"))
+	}
+
+	/// Substrate block that `number` refers to. The best block and pending state map to
+	/// the best block itself, earlier blocks to the canonical block with that number, as
+	/// Ethereum and Substrate blocks share their numbers.
+	fn native_block_id(&self, number: Option<BlockNumber>) -> Result<BlockId<B>> {
+		let header = self.select_chain.best_chain()
+			.map_err(|_| internal_err("fetch header failed"))?;
+		let best: u32 = header.number().clone().unique_saturated_into();
+		if let Some(BlockNumber::Pending) = number {
+			return Ok(BlockId::Hash(header.hash()));
+		}
+		let native_number = self.native_block_number_at(&header, number)
+			.ok_or_else(|| Error::from(EthApiError::UnknownBlock))?;
+		if native_number == best {
+			return Ok(BlockId::Hash(header.hash()));
+		}
+		if native_number > best {
+			return Err(invalid_params_err("block number is beyond the best block"));
+		}
+		Ok(BlockId::Number(native_number.into()))
+	}
+
+	/// Number of recent blocks whose transaction statuses the runtime at `id` keeps, zero
+	/// if it keeps all of them. Runtimes without the method do not prune.
+	fn status_retention(&self, id: &BlockId<B>) -> Result<u32> {
+		if !self.runtime_api_version_at_least(id, 7)? {
+			return Ok(0);
+		}
+		self.client.runtime_api()
+			.transaction_status_retention(id)
+			.map_err(|_| runtime_api_err("fetch runtime transaction status retention failed"))
+	}
+
+	/// Fail if the transaction statuses of `from` were pruned by the runtime, unless this
+	/// node reads them from archived state.
+	fn ensure_within_retention(&self, from: Option<BlockNumber>) -> Result<()> {
+		if self.archive {
+			return Ok(());
+		}
+		let header = self.select_chain.best_chain()
+			.map_err(|_| internal_err("fetch header failed"))?;
+		let retention = self.status_retention(&BlockId::Hash(header.hash()))?;
+		if retention == 0 {
+			return Ok(());
+		}
+		let best: u64 = header.number().clone().unique_saturated_into();
+		if let Some(from) = self.native_block_number_at(&header, from) {
+			check_retention_window(from as u64, best, retention as u64)?;
+		}
+		Ok(())
+	}
+
+	/// Logs matching `filter`, either in the block with its `block_hash` or in the blocks
+	/// between `from_block` and `to_block`, which default to the best block.
+	fn logs_matching(&self, filter: &Filter) -> Result<Vec<Log>> {
+		let header = self.select_chain.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
+		let id = BlockId::Hash(header.hash());
+
+		let blocks = match filter.block_hash {
+			Some(hash) => self.client.runtime_api()
+				.block_by_hash(&id, hash)
+				.map_err(|_| runtime_api_err("fetch runtime block failed"))?
+				.into_iter()
+				.collect(),
+			None => {
+				let best: u32 = header.number().clone().unique_saturated_into();
+				let from = self.native_block_number_at(&header, filter.from_block.clone()).unwrap_or(best);
+				let to = self.native_block_number_at(&header, filter.to_block.clone()).unwrap_or(best);
+				validate_range(from as u64, to as u64, best as u64, self.max_log_block_range)?;
+				self.blocks_in_range(&id, from, to)?
+			},
+		};
+		self.filtered_logs(&id, blocks, filter)
+	}
+
+	/// Block whose state holds the statuses of the transactions of `block`: the block
+	/// that stored them on archive nodes, `best` otherwise.
+	fn statuses_at(&self, best: &BlockId<B>, block: &EthereumBlock) -> BlockId<B> {
+		if self.archive {
+			BlockId::Number(block.header.number.low_u32().into())
+		} else {
+			best.clone()
+		}
+	}
+
+	/// Logs of the transactions in `blocks` that match `filter`, in chain order.
+	fn filtered_logs(&self, at: &BlockId<B>, blocks: Vec<EthereumBlock>, filter: &Filter) -> Result<Vec<Log>> {
+		let mut logs = Vec::new();
+		for block in blocks {
+			if !bloom_may_match(filter, &block.header.logs_bloom) {
+				continue;
+			}
+			let statuses_at = self.statuses_at(at, &block);
+			let legacy = has_legacy_statuses(&*self.client, &statuses_at)?;
+			let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+			for transaction in &block.transactions {
+				let hash = H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice());
+				let status = transaction_status_at(&*self.client, &statuses_at, hash, legacy)?;
+				if let Some(status) = status {
+					logs.extend(
+						receipt_logs(block_hash, block.header.number, &status)
+							.into_iter()
+							.filter(|log| filter_matches(filter, log))
+					);
+				}
+			}
+		}
+		if let Some(limit) = filter.limit {
+			let skip = logs.len().saturating_sub(limit);
+			logs.drain(..skip);
+		}
+		Ok(logs)
+	}
 
-		let mut native_number: Option<u32> = None;
-
-		if let Some(number) = number {
-			match number {
-				BlockNumber::Hash { hash, .. } => {
-					if let Ok(Some(block)) = self.client.runtime_api().block_by_hash(
-						&BlockId::Hash(header.hash()),
-						hash
-					) {
-						native_number = Some(block.header.number.as_u32());
-					}
-				},
-				BlockNumber::Num(_) => {
-					if let Some(number) = number.to_min_block_num() {
-						native_number = Some(number.unique_saturated_into());
-					}
-				},
-				BlockNumber::Latest => {
-					native_number = Some(
-						header.number().clone().unique_saturated_into() as u32
+	/// Transactions of the Ethereum block with the given hash, in the form requested
+	/// by the `full` flag of the `eth_getBlockBy*` methods.
+	fn block_transactions(
+		&self,
+		at: &BlockId<B>,
+		block: &EthereumBlock,
+		full: bool,
+	) -> Result<BlockTransactions> {
+		let at = &self.statuses_at(at, block);
+		if full {
+			let legacy = has_legacy_statuses(&*self.client, at)?;
+			let statuses = block.transactions.iter()
+				.map(|transaction| {
+					let hash = H256::from_slice(
+						Keccak256::digest(&rlp::encode(transaction)).as_slice()
 					);
-				},
-				BlockNumber::Earliest => {
-					native_number = Some(1);
-				},
-				BlockNumber::Pending => {
-					native_number = None;
+					transaction_status_at(&*self.client, at, hash, legacy)
+				})
+				.collect::<Result<Vec<_>>>()?;
+			return Ok(BlockTransactions::Full(full_transactions_build(block, statuses)));
+		}
+
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+		self.ensure_runtime_method(at, "block_transaction_hashes")?;
+		// Pruned blocks no longer list their transaction hashes.
+		let hashes = self.client.runtime_api()
+			.block_transaction_hashes(at, hash)
+			.map_err(|_| runtime_api_err("fetch runtime block transaction hashes failed"))?
+			.unwrap_or_else(|| {
+				block.transactions.iter()
+					.map(|transaction| {
+						H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice())
+					})
+					.collect()
+			});
+		Ok(BlockTransactions::Hashes(hashes))
+	}
+
+	/// Build the RPC form of `block`, with full transactions or only their hashes.
+	fn build_rich_block(&self, at: &BlockId<B>, block: EthereumBlock, full: bool) -> Result<RichBlock> {
+		let transactions = self.block_transactions(at, &block, full)?;
+		Ok(rich_block_build(block, transactions))
+	}
+
+	/// Fetch the Ethereum blocks `from..=to` at `id` in ascending order, skipping unknown
+	/// numbers. Runtimes providing `blocks_in_range` are queried in batches, older ones
+	/// block by block.
+	pub fn blocks_in_range(&self, id: &BlockId<B>, from: u32, to: u32) -> Result<Vec<EthereumBlock>> {
+		let use_batches = self.runtime_api_version_at_least(id, 3)?;
+		let api = self.client.runtime_api();
+		if !use_batches {
+			let mut blocks = Vec::new();
+			for number in from..=to {
+				if let Some(block) = api.block_by_number(id, number)
+					.map_err(|_| runtime_api_err("fetch runtime block failed"))?
+				{
+					blocks.push(block);
 				}
-			};
-		} else {
-			native_number = Some(
-				header.number().clone().unique_saturated_into() as u32
+			}
+			return Ok(blocks);
+		}
+
+		let mut blocks = Vec::new();
+		let mut start = from;
+		while start <= to {
+			let end = to.min(start.saturating_add(MAX_BLOCKS_IN_RANGE - 1));
+			blocks.extend(
+				api.blocks_in_range(id, start, end)
+					.map_err(|_| runtime_api_err("fetch runtime blocks in range failed"))?
 			);
+			if end == to {
+				break;
+			}
+			start = end + 1;
+		}
+		Ok(blocks)
+	}
+
+	/// Transaction `hash` with its block and status, as stored at `at`.
+	fn transaction_and_block(
+		&self,
+		at: &BlockId<B>,
+		hash: H256,
+	) -> Result<Option<(EthereumTransaction, EthereumBlock, TransactionStatus)>> {
+		let legacy = has_legacy_statuses(&*self.client, at)?;
+		let api = self.client.runtime_api();
+		#[allow(deprecated)]
+		let found = if legacy {
+			api.transaction_by_hash_before_version_5(at, hash).map(upgrade_status)
+		} else {
+			api.transaction_by_hash(at, hash)
+		};
+		found.map_err(|_| runtime_api_err("fetch runtime transaction status failed"))
+	}
+
+	/// Transaction with the given hash, with its block and status, as of the best block
+	/// `best`. Archive nodes look for statuses pruned by then in the state of older
+	/// blocks, each of which holds the statuses of the `retention` blocks up to it.
+	fn find_transaction(
+		&self,
+		best: &B::Header,
+		hash: H256,
+	) -> Result<Option<(EthereumTransaction, EthereumBlock, TransactionStatus)>> {
+		let at = BlockId::Hash(best.hash());
+		let found = self.transaction_and_block(&at, hash)?;
+		if found.is_some() || !self.archive {
+			return Ok(found);
+		}
+		let retention = self.status_retention(&at)?;
+		let mut number: u32 = best.number().clone().unique_saturated_into();
+		while retention > 0 && number > retention {
+			number -= retention;
+			let found = self.transaction_and_block(&BlockId::Number(number.into()), hash)?;
+			if found.is_some() {
+				return Ok(found);
+			}
+		}
+		Ok(None)
+	}
+
+	/// Whether the runtime at `id` implements at least `version` of the Ethereum runtime API.
+	fn runtime_api_version_at_least(&self, id: &BlockId<B>, version: u32) -> Result<bool> {
+		self.client.runtime_api()
+			.has_api_with::<dyn EthereumRuntimeApi<B>, _>(id, |v| v >= version)
+			.map_err(|_| runtime_api_err("fetch runtime api version failed"))
+	}
+
+	/// Ensure the runtime at `id` provides the given Ethereum runtime API method.
+	fn ensure_runtime_method(&self, id: &BlockId<B>, method: &str) -> Result<()> {
+		let supported = self.client.runtime_api()
+			.has_api_with::<dyn EthereumRuntimeApi<B>, _>(
+				id,
+				|version| check_runtime_method(version, method).is_ok(),
+			)
+			.map_err(|_| runtime_api_err("fetch runtime api version failed"))?;
+
+		if supported {
+			Ok(())
+		} else {
+			Err(unsupported_runtime_method_err(method))
+		}
+	}
+
+	/// Execute a call request against the block `number` refers to without persisting any
+	/// changes, returning its output and the gas it used. Requests without a recipient
+	/// create a contract, and return the code it would deploy.
+	fn execute_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> Result<(Vec<u8>, U256)> {
+		check_init_code_size(&request, self.max_init_code_size)?;
+		let permit = self.acquire_request_permit()?;
+
+		let id = self.native_block_id(number)?;
+		if request.to.is_none() {
+			self.ensure_runtime_method(&id, "create")?;
 		}
-		Ok(native_number)
+
+		let from = request.from.unwrap_or_default();
+		let to = request.to;
+		let gas_price = match request.gas_price {
+			Some(gas_price) => gas_price,
+			None => self.default_gas_price(&id)?,
+		};
+		let gas_limit = request.gas.unwrap_or(U256::max_value());
+		let value = request.value.unwrap_or_default();
+		let data = request.data.map(|d| d.0).unwrap_or_default();
+		let nonce = request.nonce;
+
+		let reports_errors = self.runtime_api_version_at_least(&id, 2)?;
+		let client = self.client.clone();
+		with_timeout(self.execution_timeout.as_ref(), move || {
+			// Held until the execution finishes, even if the caller timed out.
+			let _permit = permit;
+			let to = match to {
+				Some(to) => to,
+				None => return client.runtime_api()
+					.create(&id, from, data, value, gas_limit, gas_price, nonce)
+					.map_err(|_| runtime_api_err("runtime trapped while executing create"))?
+					.map_err(execution_err),
+			};
+			if !reports_errors {
+				#[allow(deprecated)]
+				let result = client.runtime_api()
+					.call_before_version_2(
+						&id, from, to, data, value, gas_limit, gas_price, nonce,
+					)
+					.map_err(|_| runtime_api_err("runtime trapped while executing call"))?;
+				return result.ok_or_else(|| execution_err(ExecutionError::Failed));
+			}
+
+			client.runtime_api()
+				.call(&id, from, to, data, value, gas_limit, gas_price, nonce)
+				.map_err(|_| runtime_api_err("runtime trapped while executing call"))?
+				.map_err(execution_err)
+		})
+	}
+
+	/// Gas price used when a call request against the block `id` omits one: the minimum
+	/// gas price at that block. `pending` resolves to the best block, whose minimum gas
+	/// price the next block will be built with.
+	fn default_gas_price(&self, id: &BlockId<B>) -> Result<U256> {
+		self.client
+			.runtime_api()
+			.gas_price(id)
+			.map_err(|_| runtime_api_err("fetch runtime gas price failed"))
 	}
 }
 
-impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
-	C: ProvideRuntimeApi<B> + StorageProvider<B,BE>,
+impl<B, C, SC, P, CT> EthApiT for EthApi<B, C, SC, P, CT> where
+	C: ProvideRuntimeApi<B>,
 	C::Api: EthereumRuntimeApi<B>,
-	BE: Backend<B> + 'static,
-	BE::State: StateBackend<BlakeTwo256>,
 	B: BlockT<Hash=H256> + Send + Sync + 'static,
 	C: Send + Sync + 'static,
 	SC: SelectChain<B> + Clone + 'static,
@@ -205,7 +1160,10 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 	}
 
 	fn syncing(&self) -> Result<SyncStatus> {
-		unimplemented!("syncing");
+		let header = self.select_chain.best_chain()
+			.map_err(|_| internal_err("fetch header failed"))?;
+		let current: u64 = header.number().clone().unique_saturated_into();
+		Ok(sync_status_build(&*self.sync_status, current))
 	}
 
 	fn hashrate(&self) -> Result<U256> {
@@ -221,7 +1179,7 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			self.client
 			.runtime_api()
 			.author(&BlockId::Hash(header.hash()))
-			.map_err(|_| internal_err("fetch runtime chain id failed"))?.into()
+			.map_err(|_| runtime_api_err("fetch runtime author failed"))?.into()
 		)
 	}
 
@@ -230,10 +1188,7 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 	}
 
 	fn chain_id(&self) -> Result<Option<U64>> {
-		let header = self.select_chain.best_chain()
-			.map_err(|_| internal_err("fetch header failed"))?;
-		Ok(Some(self.client.runtime_api().chain_id(&BlockId::Hash(header.hash()))
-				.map_err(|_| internal_err("fetch runtime chain id failed"))?.into()))
+		Ok(Some(self.cached_chain_id()?.into()))
 	}
 
 	fn gas_price(&self) -> Result<U256> {
@@ -245,13 +1200,40 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			self.client
 				.runtime_api()
 				.gas_price(&BlockId::Hash(header.hash()))
-				.map_err(|_| internal_err("fetch runtime chain id failed"))?
+				.map_err(|_| runtime_api_err("fetch runtime gas price failed"))?
 				.into(),
 		)
 	}
 
+	fn fee_history(
+		&self,
+		block_count: U256,
+		newest_block: BlockNumber,
+		reward_percentiles: Option<Vec<f64>>,
+	) -> Result<FeeHistory> {
+		let header = self.select_chain.best_chain()
+			.map_err(|_| internal_err("fetch header failed"))?;
+		let id = BlockId::Hash(header.hash());
+		let best: u32 = header.number().clone().unique_saturated_into();
+		let newest = self.native_block_number_at(&header, Some(newest_block)).unwrap_or(best);
+		let count = block_count.min(U256::from(MAX_BLOCK_RANGE)).as_u32();
+		// An empty history is still checked as ending at a known block.
+		let oldest = newest.saturating_add(1).saturating_sub(count.max(1));
+		validate_range(oldest as u64, newest as u64, best as u64, MAX_BLOCK_RANGE)?;
+		let blocks = if count == 0 {
+			Vec::new()
+		} else {
+			self.blocks_in_range(&id, oldest, newest)?
+		};
+		let gas_price = self.client.runtime_api()
+			.gas_price(&id)
+			.map_err(|_| runtime_api_err("fetch runtime gas price failed"))?;
+
+		Ok(fee_history_build(oldest, &blocks, gas_price, reward_percentiles.as_deref()))
+	}
+
 	fn accounts(&self) -> Result<Vec<H160>> {
-		Ok(vec![])
+		Ok(self.keystore.as_ref().map(keystore_addresses).unwrap_or_default())
 	}
 
 	fn block_number(&self) -> Result<U256> {
@@ -263,36 +1245,34 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 	}
 
 	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
-		if let Ok(Some(native_number)) = self.native_block_number(number) {
-			return Ok(
-				self.client
-					.runtime_api()
-					.account_basic(&BlockId::Number(native_number.into()), address)
-					.map_err(|_| internal_err("fetch runtime chain id failed"))?
-					.balance.into(),
-			);
-		}
-		Ok(U256::zero())
+		let id = self.native_block_id(number)?;
+		Ok(
+			self.client
+				.runtime_api()
+				.account_basic(&id, address)
+				.map_err(|_| runtime_api_err("fetch runtime account basic failed"))?
+				.balance,
+		)
 	}
 
 	fn proof(&self, _: H160, _: Vec<H256>, _: Option<BlockNumber>) -> BoxFuture<EthAccount> {
-		unimplemented!("proof");
+		Box::new(future::result(Err(
+			EthApiError::Unsupported("state proofs not supported".to_string()).into()
+		)))
 	}
 
 	fn storage_at(&self, address: H160, index: U256, number: Option<BlockNumber>) -> Result<H256> {
-		if let Ok(Some(native_number)) = self.native_block_number(number) {
-			return Ok(
-				self.client
-					.runtime_api()
-					.storage_at(&BlockId::Number(native_number.into()), address, index)
-					.map_err(|_| internal_err("fetch runtime chain id failed"))?
-					.into(),
-			);
-		}
-		Ok(H256::default())
+		let id = self.native_block_id(number)?;
+		Ok(
+			self.client
+				.runtime_api()
+				.storage_at(&id, address, index)
+				.map_err(|_| runtime_api_err("fetch runtime account storage failed"))?
+				.into(),
+		)
 	}
 
-	fn block_by_hash(&self, hash: H256, _: bool) -> Result<Option<RichBlock>> {
+	fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
 		let header = self.select_chain.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
 
@@ -300,37 +1280,50 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			&BlockId::Hash(header.hash()),
 			hash
 		) {
-			Ok(Some(rich_block_build(block)))
+			Ok(Some(self.build_rich_block(&BlockId::Hash(header.hash()), block, full)?))
 		} else {
 			Ok(None)
 		}
 	}
 
-	fn block_by_number(&self, number: BlockNumber, _: bool) -> Result<Option<RichBlock>> {
+	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<RichBlock>> {
 		let header = self.select_chain.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
-		if let Ok(Some(native_number)) = self.native_block_number(Some(number)) {
+		if let Some(native_number) = self.native_block_number_at(&header, Some(number)) {
 			if let Ok(Some(block)) = self.client.runtime_api().block_by_number(
 				&BlockId::Hash(header.hash()),
 				native_number
 			) {
-				return Ok(Some(rich_block_build(block)));
+				return Ok(Some(self.build_rich_block(&BlockId::Hash(header.hash()), block, full)?));
 			}
 		}
 		Ok(None)
 	}
 
 	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
-		if let Ok(Some(native_number)) = self.native_block_number(number) {
-			return Ok(
-				self.client
-					.runtime_api()
-					.account_basic(&BlockId::Number(native_number.into()), address)
-		   			.map_err(|_| internal_err("fetch runtime account basic failed"))?
-					   .nonce.into()
-			);
+		if let Some(BlockNumber::Pending) = number {
+			let header = self.select_chain.best_chain()
+				.map_err(|_| internal_err("fetch header failed"))?;
+			let id = BlockId::Hash(header.hash());
+			let on_chain = self.client.runtime_api()
+				.account_basic(&id, address)
+				.map_err(|_| runtime_api_err("fetch runtime account basic failed"))?
+				.nonce;
+			let transactions = self.ready_transactions(&id, MAX_TXPOOL_CONTENT)?;
+			let pooled = transactions.iter()
+				.zip(self.senders.senders(&transactions))
+				.filter(|(_, sender)| *sender == Some(address))
+				.map(|(transaction, _)| transaction.nonce);
+			return Ok(pending_nonce(on_chain, pooled));
 		}
-		Ok(U256::zero())
+		let id = self.native_block_id(number)?;
+		Ok(
+			self.client
+				.runtime_api()
+				.account_basic(&id, address)
+				.map_err(|_| runtime_api_err("fetch runtime account basic failed"))?
+				.nonce,
+		)
 	}
 
 	fn block_transaction_count_by_hash(&self, hash: H256) -> Result<Option<U256>> {
@@ -350,7 +1343,7 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			.map_err(|_| internal_err("fetch header failed"))?;
 
 		let mut result = None;
-		if let Ok(Some(native_number)) = self.native_block_number(Some(number)) {
+		if let Some(native_number) = self.native_block_number_at(&header, Some(number)) {
 			result = match self.client.runtime_api()
 				.block_transaction_count_by_number(&BlockId::Hash(header.hash()), native_number) {
 				Ok(result) => result,
@@ -369,28 +1362,48 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 	}
 
 	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes> {
-		if let Ok(Some(native_number)) = self.native_block_number(number) {
-			return Ok(
-				self.client
-					.runtime_api()
-					.account_code_at(&BlockId::Number(native_number.into()), address)
-					.map_err(|_| internal_err("fetch runtime chain id failed"))?
-					.into(),
-			);
-		}
-		Ok(Bytes(vec![]))
+		let id = self.native_block_id(number)?;
+		Ok(
+			self.client
+				.runtime_api()
+				.account_code_at(&id, address)
+				.map_err(|_| runtime_api_err("fetch runtime account code failed"))?
+				.into(),
+		)
+	}
+
+	fn code_size(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let id = self.native_block_id(number)?;
+		self.ensure_runtime_method(&id, "account_code_len")?;
+		Ok(
+			self.client
+				.runtime_api()
+				.account_code_len(&id, address)
+				.map_err(|_| runtime_api_err("fetch runtime account code length failed"))?
+				.into(),
+		)
+	}
+
+	fn substrate_account(&self, address: H160, number: Option<BlockNumber>) -> Result<Option<H256>> {
+		let id = self.native_block_id(number)?;
+		self.ensure_runtime_method(&id, "substrate_account")?;
+		Ok(
+			self.client
+				.runtime_api()
+				.substrate_account(&id, address)
+				.map_err(|_| runtime_api_err("fetch runtime substrate account failed"))?
+				.map(|account| H256::from_slice(account.as_ref())),
+		)
 	}
 
 	fn send_raw_transaction(&self, bytes: Bytes) -> BoxFuture<H256> {
-		let transaction = match rlp::decode::<ethereum::Transaction>(&bytes.0[..]) {
-			Ok(transaction) => transaction,
-			Err(_) => return Box::new(
-				future::result(Err(internal_err("decode transaction failed")))
-			),
+		if let Err(err) = check_not_syncing(&*self.sync_status, &self.submission) {
+			return Box::new(future::result(Err(err)));
+		}
+		let (transaction, transaction_hash) = match decode_raw_transaction(&bytes.0[..]) {
+			Ok(decoded) => decoded,
+			Err(err) => return Box::new(future::result(Err(err))),
 		};
-		let transaction_hash = H256::from_slice(
-			Keccak256::digest(&rlp::encode(&transaction)).as_slice()
-		);
 		let header = match self.select_chain.best_chain() {
 			Ok(header) => header,
 			Err(_) => return Box::new(
@@ -398,6 +1411,16 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			),
 		};
 		let best_block_hash = header.hash();
+		let chain_min_gas_price = match self.client.runtime_api().gas_price(&BlockId::Hash(best_block_hash)) {
+			Ok(gas_price) => gas_price,
+			Err(_) => return Box::new(
+				future::result(Err(runtime_api_err("fetch runtime gas price failed")))
+			),
+		};
+		let node_min_gas_price = self.submission.min_gas_price;
+		if let Err(err) = check_gas_price(transaction.gas_price, chain_min_gas_price, node_min_gas_price) {
+			return Box::new(future::result(Err(err)));
+		}
 		Box::new(
 			self.pool
 				.submit_one(
@@ -415,66 +1438,16 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 		unimplemented!("submit_transaction");
 	}
 
-	fn call(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<Bytes> {
-		let header = self
-			.select_chain
-			.best_chain()
-			.map_err(|_| internal_err("fetch header failed"))?;
-
-		let from = request.from.unwrap_or_default();
-		let to = request.to.unwrap_or_default();
-		let gas_price = request.gas_price.unwrap_or_default();
-		let gas_limit = request.gas.unwrap_or(U256::max_value());
-		let value = request.value.unwrap_or_default();
-		let data = request.data.map(|d| d.0).unwrap_or_default();
-		let nonce = request.nonce;
-
-		let (ret, _) = self.client.runtime_api()
-			.call(
-				&BlockId::Hash(header.hash()),
-				from,
-				to,
-				data,
-				value,
-				gas_limit,
-				gas_price,
-				nonce,
-			)
-			.map_err(|_| internal_err("executing call failed"))?
-			.ok_or(internal_err("inner executing call failed"))?;
+	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes> {
+		let (ret, _) = self.execute_call(request, number)?;
 
 		Ok(Bytes(ret))
 	}
 
-	fn estimate_gas(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<U256> {
-		let header = self
-			.select_chain
-			.best_chain()
-			.map_err(|_| internal_err("fetch header failed"))?;
-
-		let from = request.from.unwrap_or_default();
-		let to = request.to.unwrap_or_default();
-		let gas_price = request.gas_price.unwrap_or_default();
-		let gas_limit = request.gas.unwrap_or(U256::max_value());
-		let value = request.value.unwrap_or_default();
-		let data = request.data.map(|d| d.0).unwrap_or_default();
-		let nonce = request.nonce;
-
-		let (_, used_gas) = self.client.runtime_api()
-			.call(
-				&BlockId::Hash(header.hash()),
-				from,
-				to,
-				data,
-				value,
-				gas_limit,
-				gas_price,
-				nonce,
-			)
-			.map_err(|_| internal_err("executing call failed"))?
-			.ok_or(internal_err("inner executing call failed"))?;
+	fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<U256> {
+		let (_, used_gas) = self.execute_call(request, number)?;
 
-		Ok(used_gas)
+		Ok(apply_gas_margin(used_gas, self.gas_estimate_margin))
 	}
 
 	fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
@@ -483,15 +1456,10 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
 
-		if let Ok(Some((transaction, block, status))) = self.client.runtime_api()
-			.transaction_by_hash(&BlockId::Hash(header.hash()), hash) {
-			return Ok(Some(transaction_build(
-				transaction,
-				block,
-				status
-			)));
-		}
-		Ok(None)
+		let found = self.find_transaction(&header, hash)?;
+		Ok(found.map(|(transaction, block, status)| {
+			transaction_build(transaction, Some(block), Some(status))
+		}))
 	}
 
 	fn transaction_by_block_hash_and_index(
@@ -504,14 +1472,31 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
 
-		let index_param = index.value() as u32;
+		let index_param = match u32::try_from(index.value()) {
+			Ok(index) => index,
+			Err(_) => return Ok(None),
+		};
 
-		if let Ok(Some((transaction, block, status))) = self.client.runtime_api()
-			.transaction_by_block_hash_and_index(&BlockId::Hash(header.hash()), hash, index_param) {
+		let mut at = BlockId::Hash(header.hash());
+		if self.archive {
+			if let Ok(Some(block)) = self.client.runtime_api().block_by_hash(&at, hash) {
+				at = self.statuses_at(&at, &block);
+			}
+		}
+		let legacy = has_legacy_statuses(&*self.client, &at)?;
+		let api = self.client.runtime_api();
+		#[allow(deprecated)]
+		let found = if legacy {
+			api.transaction_by_block_hash_and_index_before_version_5(&at, hash, index_param)
+				.map(upgrade_status)
+		} else {
+			api.transaction_by_block_hash_and_index(&at, hash, index_param)
+		};
+		if let Ok(Some((transaction, block, status))) = found {
 			return Ok(Some(transaction_build(
 				transaction,
-				block,
-				status
+				Some(block),
+				Some(status)
 			)));
 		}
 		Ok(None)
@@ -527,44 +1512,77 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 			.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
 
-		let index_param = index.value() as u32;
+		let index_param = match u32::try_from(index.value()) {
+			Ok(index) => index,
+			Err(_) => return Ok(None),
+		};
 
-		if let Ok(Some(native_number)) = self.native_block_number(Some(number)) {
-			if let Ok(Some((transaction, block, status))) = self.client.runtime_api()
-				.transaction_by_block_number_and_index(
-					&BlockId::Hash(header.hash()),
+		if let Some(native_number) = self.native_block_number_at(&header, Some(number)) {
+			let at = if self.archive {
+				BlockId::Number(native_number.into())
+			} else {
+				BlockId::Hash(header.hash())
+			};
+			let legacy = has_legacy_statuses(&*self.client, &at)?;
+			let api = self.client.runtime_api();
+			#[allow(deprecated)]
+			let found = if legacy {
+				api.transaction_by_block_number_and_index_before_version_5(
+					&at,
 					native_number,
-					index_param) {
+					index_param,
+				).map(upgrade_status)
+			} else {
+				api.transaction_by_block_number_and_index(&at, native_number, index_param)
+			};
+			if let Ok(Some((transaction, block, status))) = found {
 				return Ok(Some(transaction_build(
 					transaction,
-					block,
-					status
+					Some(block),
+					Some(status)
 				)));
 			}
 		}
 		Ok(None)
 	}
 
+	fn pending_transactions(&self) -> Result<Vec<Transaction>> {
+		let header = self.select_chain.best_chain()
+			.map_err(|_| internal_err("fetch header failed"))?;
+		let id = BlockId::Hash(header.hash());
+
+		Ok(pending_transactions_build(self.ready_transactions(&id, MAX_TXPOOL_CONTENT)?))
+	}
+
 	fn transaction_receipt(&self, hash: H256) -> Result<Option<Receipt>> {
 		let header = self.select_chain.best_chain()
 			.map_err(|_| internal_err("fetch header failed"))?;
-		let status = self.client.runtime_api()
-			.transaction_status(&BlockId::Hash(header.hash()), hash)
-			.map_err(|_| internal_err("fetch runtime transaction status failed"))?;
-		let receipt = status.map(|status| {
+		let found = self.find_transaction(&header, hash)?;
+		if found.is_none() {
+			log::debug!(
+				target: "rpc",
+				"No status for transaction {:?}, it is unknown or has been pruned",
+				hash,
+			);
+		}
+		let receipt = found.map(|(_, block, status)| {
+			let block_hash = H256::from_slice(
+				Keccak256::digest(&rlp::encode(&block.header)).as_slice()
+			);
 			Receipt {
 				transaction_hash: Some(status.transaction_hash),
 				transaction_index: Some(status.transaction_index.into()),
-				block_hash: Some(Default::default()),
+				block_hash: Some(block_hash),
 				from: Some(status.from),
 				to: status.to,
-				block_number: Some(Default::default()),
-				cumulative_gas_used: Default::default(),
-				gas_used: Some(Default::default()),
+				block_number: Some(block.header.number),
+				cumulative_gas_used: status.cumulative_gas_used,
+				gas_used: Some(status.gas_used),
 				contract_address: status.contract_address,
-				logs: Vec::new(),
+				logs: receipt_logs(block_hash, block.header.number, &status),
 				state_root: None,
-				logs_bloom: Default::default(),
+				logs_bloom: status.logs_bloom,
+				// The runtime does not record whether the execution succeeded or reverted.
 				status_code: None,
 			}
 		});
@@ -585,46 +1603,1231 @@ impl<B, C, SC, P, CT, BE> EthApiT for EthApi<B, C, SC, P, CT, BE> where
 	}
 
 	fn compilers(&self) -> Result<Vec<String>> {
-		unimplemented!("compilers");
+		Ok(Vec::new())
 	}
 
 	fn compile_lll(&self, _: String) -> Result<Bytes> {
-		unimplemented!("compile_lll");
+		Err(method_not_supported_err())
 	}
 
 	fn compile_solidity(&self, _: String) -> Result<Bytes> {
-		unimplemented!("compile_solidity");
+		Err(method_not_supported_err())
 	}
 
 	fn compile_serpent(&self, _: String) -> Result<Bytes> {
-		unimplemented!("compile_serpent");
+		Err(method_not_supported_err())
 	}
 
-	fn logs(&self, _: Filter) -> BoxFuture<Vec<Log>> {
-		unimplemented!("logs");
+	fn logs(&self, filter: Filter) -> BoxFuture<Vec<Log>> {
+		let _permit = match self.acquire_request_permit() {
+			Ok(permit) => permit,
+			Err(err) => return Box::new(future::result(Err(err))),
+		};
+		if let Err(err) = self.ensure_within_retention(filter.from_block.clone()) {
+			return Box::new(future::result(Err(err)));
+		}
+		Box::new(future::result(self.logs_matching(&filter)))
 	}
 
 	fn work(&self) -> Result<Work> {
-		Ok(Work {
-			pow_hash: H256::default(),
-			seed_hash: H256::default(),
-			target: H256::default(),
-			number: None,
-		})
+		Err(mining_not_supported_err())
 	}
 
 	fn submit_work(&self, _: H64, _: H256, _: H256) -> Result<bool> {
-		Ok(false)
+		Err(mining_not_supported_err())
 	}
 
 	fn submit_hashrate(&self, _: U256, _: H256) -> Result<bool> {
-		Ok(false)
+		Err(mining_not_supported_err())
+	}
+}
+
+impl<B, C, SC, P, CT> NetApiT for EthApi<B, C, SC, P, CT> where
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeApi<B>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+	SC: SelectChain<B> + Clone + 'static,
+	P: TransactionPool<Block=B> + Send + Sync + 'static,
+	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
+{
+	fn version(&self) -> Result<String> {
+		Ok(self.cached_chain_id()?.to_string())
+	}
+
+	fn peer_count(&self) -> Result<String> {
+		Ok(peer_count_build(self.network.as_deref()))
 	}
 
 	fn is_listening(&self) -> Result<bool> {
-		Ok(true)
+		Ok(self.network.as_ref().map_or(false, |network| network.is_listening()))
 	}
-	fn version(&self) -> Result<String> {
-		Ok(self.chain_id().unwrap().unwrap().to_string())
+}
+
+impl<B, C, SC, P, CT> TxPoolApiT for EthApi<B, C, SC, P, CT> where
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeApi<B>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+	SC: SelectChain<B> + Clone + 'static,
+	P: TransactionPool<Block=B> + FutureTransactions<B> + Send + Sync + 'static,
+	CT: ConvertTransaction<<B as BlockT>::Extrinsic> + Send + Sync + 'static,
+{
+	fn content(&self) -> Result<TxPoolResult<TransactionMap>> {
+		let header = self
+			.select_chain
+			.best_chain()
+			.map_err(|_| internal_err("fetch header failed"))?;
+
+		let id = BlockId::Hash(header.hash());
+		let pending = self.ready_transactions(&id, MAX_TXPOOL_CONTENT)?;
+		let mut futures = self.pool.future_transactions();
+		futures.truncate(MAX_TXPOOL_CONTENT);
+		let queued = self.ethereum_transactions(&id, futures)?;
+
+		Ok(TxPoolResult {
+			pending: transaction_map(pending_transactions_build(pending)),
+			queued: transaction_map(pending_transactions_build(queued)),
+		})
+	}
+
+	fn status(&self) -> Result<TxPoolResult<U256>> {
+		Ok(txpool_status(self.pool.status()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpc_core::ErrorCode;
+	use frontier_rpc_core::types::{FilterAddress, Topic};
+	use crate::mock::{MockClient, MockEthApi, MockState, eth_api, pool};
+
+	#[test]
+	fn unsupported_methods_should_be_removed() {
+		let methods = vec![
+			("eth_chainId".to_string(), ()),
+			("eth_getWork".to_string(), ()),
+			("eth_compileSolidity".to_string(), ()),
+			("eth_getProof".to_string(), ()),
+		];
+		let names = without_unsupported_methods(methods)
+			.into_iter()
+			.map(|(name, _)| name)
+			.collect::<Vec<_>>();
+
+		assert_eq!(names, vec!["eth_chainId".to_string()]);
+	}
+
+	#[test]
+	fn mining_methods_should_be_rejected() {
+		let eth = eth_api(&MockClient::new(MockState::default()), &pool());
+		let errors = vec![
+			eth.work().unwrap_err(),
+			eth.submit_work(H64::zero(), H256::zero(), H256::zero()).unwrap_err(),
+			eth.submit_hashrate(U256::zero(), H256::zero()).unwrap_err(),
+		];
+
+		for err in errors {
+			assert_eq!(err.code, ErrorCode::MethodNotFound);
+			assert_eq!(err.message, "mining not supported");
+		}
+	}
+
+	#[test]
+	fn compile_methods_should_be_rejected() {
+		let eth = eth_api(&MockClient::new(MockState::default()), &pool());
+		assert_eq!(eth.compilers(), Ok(Vec::new()));
+
+		let errors = vec![
+			eth.compile_lll(String::new()).unwrap_err(),
+			eth.compile_solidity(String::new()).unwrap_err(),
+			eth.compile_serpent(String::new()).unwrap_err(),
+		];
+
+		for err in errors {
+			assert_eq!(err.code, ErrorCode::MethodNotFound);
+			assert_eq!(err.message, "method not supported");
+		}
+	}
+
+	#[test]
+	fn pool_transactions_should_be_grouped_by_sender_and_nonce() {
+		let alice = H160::repeat_byte(0xaa);
+		let bob = H160::repeat_byte(0xbb);
+		let transaction = |from, nonce: u64| Transaction {
+			from,
+			nonce: U256::from(nonce),
+			..Default::default()
+		};
+
+		let map = transaction_map(vec![
+			transaction(alice, 1),
+			transaction(bob, 0),
+			transaction(alice, 0),
+		]);
+
+		assert_eq!(map.len(), 2);
+		assert_eq!(
+			map[&alice].keys().cloned().collect::<Vec<_>>(),
+			vec![U256::from(0), U256::from(1)]
+		);
+		assert_eq!(map[&bob].keys().cloned().collect::<Vec<_>>(), vec![U256::from(0)]);
+		assert_eq!(map[&alice][&U256::from(1)].from, alice);
+	}
+
+	#[test]
+	fn pool_status_should_split_ready_and_future() {
+		let status = txpool_status(PoolStatus {
+			ready: 1,
+			ready_bytes: 120,
+			future: 2,
+			future_bytes: 240,
+		});
+
+		assert_eq!(status, TxPoolResult { pending: U256::from(1), queued: U256::from(2) });
+	}
+
+	#[test]
+	fn valid_block_range_should_be_accepted() {
+		assert_eq!(validate_range(3, 3, 10, 4), Ok((3, 3)));
+		assert_eq!(validate_range(7, 10, 10, 4), Ok((7, 10)));
+	}
+
+	#[test]
+	fn reversed_block_range_should_be_rejected() {
+		let err = validate_range(5, 4, 10, 4).unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+		assert_eq!(err.message, "invalid block range: from block is after to block");
+	}
+
+	#[test]
+	fn oversized_block_range_should_be_rejected() {
+		let err = validate_range(1, 5, 10, 4).unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+		assert_eq!(err.message, "invalid block range: range exceeds the maximum of 4 blocks");
+	}
+
+	#[test]
+	fn block_range_beyond_best_block_should_be_rejected() {
+		let err = validate_range(9, 11, 10, 4).unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+		assert_eq!(err.message, "invalid block range: to block is beyond the best block");
+	}
+
+	#[test]
+	fn init_code_above_limit_should_be_rejected() {
+		let create = |size| CallRequest {
+			data: Some(Bytes(vec![0x60; size])),
+			..Default::default()
+		};
+
+		assert!(check_init_code_size(&create(MAX_INIT_CODE_SIZE), MAX_INIT_CODE_SIZE).is_ok());
+		let err = check_init_code_size(&create(MAX_INIT_CODE_SIZE + 1), MAX_INIT_CODE_SIZE)
+			.unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+
+		let call = CallRequest {
+			to: Some(H160::repeat_byte(0x11)),
+			..create(MAX_INIT_CODE_SIZE + 1)
+		};
+		assert!(check_init_code_size(&call, MAX_INIT_CODE_SIZE).is_ok());
+	}
+
+	#[test]
+	fn gas_price_at_floor_should_be_accepted() {
+		assert_eq!(check_gas_price(U256::from(10), U256::from(10), U256::from(5)), Ok(()));
+		assert_eq!(check_gas_price(U256::from(10), U256::from(5), U256::from(10)), Ok(()));
+	}
+
+	#[test]
+	fn gas_price_below_floor_should_be_rejected() {
+		let below_chain = check_gas_price(U256::from(9), U256::from(10), U256::zero()).unwrap_err();
+		assert_eq!(below_chain.code, ErrorCode::ServerError(-32000));
+		assert_eq!(below_chain.message, "transaction underpriced");
+
+		let below_node = check_gas_price(U256::from(9), U256::from(1), U256::from(10)).unwrap_err();
+		assert_eq!(below_node.message, "transaction underpriced");
+	}
+
+	/// Syncing from the first to the second block, or synced.
+	struct StubSyncStatus(Option<(u64, u64)>);
+
+	impl SyncStatusProvider for StubSyncStatus {
+		fn is_major_syncing(&self) -> bool {
+			self.0.is_some()
+		}
+
+		fn sync_range(&self) -> Option<(u64, u64)> {
+			self.0
+		}
+	}
+
+	#[test]
+	fn transactions_should_be_rejected_while_syncing() {
+		let err = check_not_syncing(&StubSyncStatus(Some((0, 100))), &SubmissionConfig::default()).unwrap_err();
+		assert_eq!(err.code, ErrorCode::ServerError(-32000));
+		assert_eq!(err.message, "node is syncing");
+
+		assert_eq!(check_not_syncing(&StubSyncStatus(None), &SubmissionConfig::default()), Ok(()));
+	}
+
+	#[test]
+	fn transactions_should_be_accepted_while_syncing_if_configured() {
+		let config = SubmissionConfig { accept_while_syncing: true, ..Default::default() };
+		assert_eq!(check_not_syncing(&StubSyncStatus(Some((0, 100))), &config), Ok(()));
+	}
+
+	#[test]
+	fn raw_transactions_sent_while_syncing_should_not_reach_the_pool() {
+		let secret = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let client = MockClient::new(MockState::default());
+		let pool = pool();
+		let eth = EthApi::new(
+			Arc::new(client.clone()),
+			client.clone(),
+			pool.clone(),
+			client.clone(),
+			false,
+			Arc::new(StubSyncStatus(Some((0, 100)))),
+			SubmissionConfig::default(),
+		);
+
+		let err = eth.send_raw_transaction(Bytes(rlp::encode(&signed_transfer(&secret, 0))))
+			.wait()
+			.unwrap_err();
+
+		assert_eq!(err.code, ErrorCode::ServerError(-32000));
+		assert_eq!(err.message, "node is syncing");
+		assert_eq!(pool.status().ready, 0);
+		assert_eq!(pool.status().future, 0);
+	}
+
+	#[test]
+	fn execution_within_timeout_should_return_its_result() {
+		let timeout = ExecutionTimeout::new(Duration::from_secs(5), 1);
+		assert_eq!(with_timeout(Some(&timeout), || Ok(42)), Ok(42));
+		assert_eq!(with_timeout(Some(&timeout), || Ok(43)), Ok(43));
+		assert_eq!(with_timeout(None, || Ok(42)), Ok(42));
+	}
+
+	#[test]
+	fn long_running_execution_should_time_out() {
+		let timeout = ExecutionTimeout::new(Duration::from_millis(10), 1);
+		let err = with_timeout(Some(&timeout), || {
+			thread::sleep(Duration::from_secs(1));
+			Ok(())
+		}).unwrap_err();
+
+		assert_eq!(err.code, ErrorCode::ServerError(-32000));
+		assert_eq!(err.message, "execution timed out");
+	}
+
+	#[test]
+	fn version_1_runtime_should_support_original_methods_only() {
+		for method in &["chain_id", "account_basic", "block_by_number", "call"] {
+			assert_eq!(check_runtime_method(1, method), Ok(()));
+		}
+		for method in &["account_code_len", "block_transaction_hashes", "extrinsic_filter"] {
+			let err = check_runtime_method(1, method).unwrap_err();
+			assert_eq!(err.code, ErrorCode::MethodNotFound);
+			assert_eq!(err.message, format!("method {} not supported by runtime", method));
+			assert_eq!(check_runtime_method(2, method), Ok(()));
+		}
+	}
+
+	#[test]
+	fn version_2_runtime_should_not_support_blocks_in_range() {
+		assert!(check_runtime_method(2, "blocks_in_range").is_err());
+		assert_eq!(check_runtime_method(3, "blocks_in_range"), Ok(()));
+	}
+
+	fn block_with_transfer() -> EthereumBlock {
+		let transaction = EthereumTransaction {
+			nonce: U256::zero(),
+			gas_price: U256::from(1),
+			gas_limit: U256::from(21_000),
+			action: ethereum::TransactionAction::Call(H160::repeat_byte(0x11)),
+			value: U256::from(5),
+			input: Vec::new(),
+			signature: ethereum::TransactionSignature::new(
+				27, H256::repeat_byte(0x01), H256::repeat_byte(0x01),
+			).unwrap(),
+		};
+		EthereumBlock {
+			header: ethereum::Header {
+				parent_hash: H256::repeat_byte(0xaa),
+				ommers_hash: H256::zero(),
+				beneficiary: H160::repeat_byte(0xbb),
+				state_root: H256::repeat_byte(0xcc),
+				transactions_root: H256::repeat_byte(0xdd),
+				receipts_root: H256::repeat_byte(0xee),
+				logs_bloom: Default::default(),
+				difficulty: U256::zero(),
+				number: U256::from(7),
+				gas_limit: U256::from(8_000_000),
+				gas_used: U256::from(21_000),
+				timestamp: 1_600_000_000,
+				extra_data: H256::zero(),
+				mix_hash: H256::zero(),
+				nonce: H64::zero(),
+			},
+			transactions: vec![transaction],
+			ommers: Vec::new(),
+		}
+	}
+
+	fn transfer_status(block: &EthereumBlock) -> TransactionStatus {
+		TransactionStatus {
+			transaction_hash: H256::from_slice(
+				Keccak256::digest(&rlp::encode(&block.transactions[0])).as_slice()
+			),
+			transaction_index: 0,
+			from: H160::repeat_byte(0xaa),
+			to: Some(H160::repeat_byte(0x11)),
+			contract_address: None,
+			gas_used: U256::from(21_000),
+			cumulative_gas_used: U256::from(21_000),
+			log_index: 0,
+			logs: Vec::new(),
+			logs_bloom: Default::default(),
+		}
+	}
+
+	#[test]
+	fn raw_transaction_should_decode_with_its_hash() {
+		let transaction = block_with_transfer().transactions[0].clone();
+		let bytes = rlp::encode(&transaction);
+
+		let (decoded, hash) = decode_raw_transaction(&bytes).unwrap();
+
+		assert_eq!(decoded, transaction);
+		assert_eq!(hash, H256::from_slice(Keccak256::digest(&bytes).as_slice()));
+	}
+
+	#[test]
+	fn malformed_raw_transaction_should_be_invalid_params() {
+		let bytes = rlp::encode(&block_with_transfer().transactions[0]);
+
+		let err = decode_raw_transaction(&bytes[..bytes.len() - 1]).unwrap_err();
+
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+		assert_eq!(err.message, "decode transaction failed");
+	}
+
+	#[test]
+	fn stored_sender_should_be_used_without_recovery() {
+		let block = block_with_transfer();
+		let transaction = block.transactions[0].clone();
+		let recovered = recover_signer(&transaction).map(|(signer, _)| signer);
+		assert_ne!(recovered, Some(H160::repeat_byte(0xaa)));
+
+		let built = transaction_build(transaction, Some(block.clone()), Some(transfer_status(&block)));
+
+		assert_eq!(built.from, H160::repeat_byte(0xaa));
+	}
+
+	#[test]
+	fn rich_block_should_map_header_fields() {
+		let block = block_with_transfer();
+		let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+		let transaction_hash = H256::repeat_byte(0x42);
+
+		let rich = rich_block_build(block, BlockTransactions::Hashes(vec![transaction_hash]));
+
+		assert_eq!(rich.inner.hash, Some(block_hash));
+		assert_eq!(rich.inner.parent_hash, H256::repeat_byte(0xaa));
+		assert_eq!(rich.inner.state_root, H256::repeat_byte(0xcc));
+		assert_eq!(rich.inner.transactions_root, H256::repeat_byte(0xdd));
+		assert_eq!(rich.inner.gas_used, U256::from(21_000));
+		assert_eq!(rich.inner.gas_limit, U256::from(8_000_000));
+		assert_eq!(rich.inner.timestamp, U256::from(1_600_000_000));
+		match rich.inner.transactions {
+			BlockTransactions::Hashes(hashes) => assert_eq!(hashes, vec![transaction_hash]),
+			_ => panic!("expected transaction hashes"),
+		}
+	}
+
+	#[test]
+	fn full_and_summary_blocks_should_only_differ_in_transactions() {
+		let block = block_with_transfer();
+		let summary = rich_block_build(block.clone(), BlockTransactions::Hashes(vec![H256::zero()]));
+		let full = rich_block_build(block.clone(), BlockTransactions::Full(
+			full_transactions_build(&block, vec![Some(transfer_status(&block))])
+		));
+
+		let mut summary = serde_json::to_value(&summary).unwrap();
+		let mut full = serde_json::to_value(&full).unwrap();
+		assert_ne!(summary["transactions"], full["transactions"]);
+		summary.as_object_mut().unwrap().remove("transactions");
+		full.as_object_mut().unwrap().remove("transactions");
+		assert_eq!(summary, full);
+		assert_eq!(summary["size"], serde_json::to_value(U256::from(rlp::encode(&block).len())).unwrap());
+	}
+
+	#[test]
+	fn full_block_transactions_should_be_expanded() {
+		let block = block_with_transfer();
+		let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+		let transaction_hash = H256::from_slice(
+			Keccak256::digest(&rlp::encode(&block.transactions[0])).as_slice()
+		);
+
+		let transactions = full_transactions_build(&block, vec![Some(transfer_status(&block))]);
+
+		assert_eq!(transactions.len(), 1);
+		assert_eq!(transactions[0].hash, transaction_hash);
+		assert_eq!(transactions[0].block_hash, Some(block_hash));
+		assert_eq!(transactions[0].block_number, Some(U256::from(7)));
+		assert_eq!(transactions[0].transaction_index, Some(U256::zero()));
+		assert_eq!(transactions[0].from, H160::repeat_byte(0xaa));
+		assert_eq!(transactions[0].value, U256::from(5));
+	}
+
+	#[test]
+	fn block_numbers_should_resolve_against_given_best_block() {
+		let best = 10;
+		let number_of_hash = |hash: H256| if hash == H256::repeat_byte(0x09) { Some(9) } else { None };
+		let resolve = |number| resolve_block_number(number, best, number_of_hash);
+
+		assert_eq!(resolve(None), Some(10));
+		assert_eq!(resolve(Some(BlockNumber::Latest)), Some(10));
+		assert_eq!(resolve(Some(BlockNumber::Earliest)), Some(1));
+		assert_eq!(resolve(Some(BlockNumber::Pending)), None);
+		assert_eq!(resolve(Some(BlockNumber::Num(7))), Some(7));
+		assert_eq!(
+			resolve(Some(BlockNumber::Hash { hash: H256::repeat_byte(0x09), require_canonical: false })),
+			Some(9)
+		);
+		assert_eq!(
+			resolve(Some(BlockNumber::Hash { hash: H256::repeat_byte(0x0b), require_canonical: false })),
+			None
+		);
+	}
+
+	struct StubNetwork(usize);
+
+	impl NetworkStatusProvider for StubNetwork {
+		fn peer_count(&self) -> usize {
+			self.0
+		}
+
+		fn is_listening(&self) -> bool {
+			true
+		}
+	}
+
+	#[test]
+	fn peer_count_should_be_reported_as_hex() {
+		assert_eq!(peer_count_build(Some(&StubNetwork(3))), "0x3");
+		assert_eq!(peer_count_build(None), "0x0");
+	}
+
+	#[test]
+	fn synced_node_should_not_report_sync_info() {
+		assert_eq!(sync_status_build(&StubSyncStatus(None), 100), SyncStatus::None);
+	}
+
+	#[test]
+	fn syncing_node_should_report_sync_info() {
+		assert_eq!(
+			sync_status_build(&StubSyncStatus(Some((10, 500))), 100),
+			SyncStatus::Info(SyncInfo {
+				starting_block: U256::from(10),
+				current_block: U256::from(100),
+				highest_block: U256::from(500),
+				warp_chunks_amount: None,
+				warp_chunks_processed: None,
+			})
+		);
+	}
+
+	#[test]
+	fn requests_beyond_limit_should_be_rejected() {
+		let limiter = Arc::new(RequestLimiter::new(2));
+		let first = RequestLimiter::try_acquire(&limiter).unwrap();
+		let _second = RequestLimiter::try_acquire(&limiter).unwrap();
+
+		let err = RequestLimiter::try_acquire(&limiter).err().unwrap();
+		assert_eq!(err.code, ErrorCode::ServerError(-32005));
+		assert_eq!(err.message, "server busy");
+
+		drop(first);
+		assert!(RequestLimiter::try_acquire(&limiter).is_ok());
+	}
+
+	#[test]
+	fn logs_below_retention_window_should_be_rejected() {
+		let err = check_retention_window(900, 1000, 100).unwrap_err();
+		assert_eq!(err.code, ErrorCode::ServerError(-32000));
+		assert_eq!(err.message, "requested range is older than the pruning window");
+
+		assert_eq!(check_retention_window(901, 1000, 100), Ok(()));
+		assert_eq!(check_retention_window(0, 100, 100), Ok(()));
+	}
+
+	#[test]
+	fn version_3_runtime_should_not_support_substrate_account() {
+		assert!(check_runtime_method(3, "substrate_account").is_err());
+		assert_eq!(check_runtime_method(4, "substrate_account"), Ok(()));
+	}
+
+	#[test]
+	fn execution_errors_should_map_to_rpc_errors() {
+		let out_of_gas = execution_err(ExecutionError::OutOfGas);
+		assert_eq!(out_of_gas.code, ErrorCode::ServerError(-32000));
+		assert_eq!(out_of_gas.message, "out of gas");
+
+		let reverted = execution_err(ExecutionError::Reverted(vec![0x08, 0xc3, 0x79, 0xa0]));
+		assert_eq!(reverted.code, ErrorCode::ServerError(3));
+		assert_eq!(reverted.data, Some(jsonrpc_core::Value::String("0x08c379a0".to_string())));
+
+		let failed = execution_err(ExecutionError::Failed);
+		assert_eq!(failed.code, ErrorCode::ServerError(-32015));
+		assert_eq!(failed.data, None);
+	}
+
+	#[test]
+	fn gas_estimate_should_include_margin() {
+		assert_eq!(apply_gas_margin(U256::from(21_000), 0), U256::from(21_000));
+		assert_eq!(apply_gas_margin(U256::from(21_000), 10), U256::from(23_100));
+		assert_eq!(apply_gas_margin(U256::max_value(), 10), U256::max_value());
+	}
+
+	#[test]
+	fn receipt_logs_should_be_numbered_in_block_and_transaction() {
+		let log = |byte| ethereum::Log {
+			address: H160::repeat_byte(byte),
+			topics: vec![H256::repeat_byte(byte)],
+			data: vec![byte],
+		};
+		let status = TransactionStatus {
+			transaction_hash: H256::repeat_byte(0x01),
+			transaction_index: 1,
+			from: H160::repeat_byte(0xaa),
+			to: None,
+			contract_address: None,
+			gas_used: U256::zero(),
+			cumulative_gas_used: U256::zero(),
+			log_index: 2,
+			logs: vec![log(0x10), log(0x11), log(0x12)],
+			logs_bloom: Default::default(),
+		};
+
+		let logs = receipt_logs(H256::repeat_byte(0xbb), U256::from(7), &status);
+
+		assert_eq!(
+			logs.iter().map(|log| log.log_index.unwrap()).collect::<Vec<_>>(),
+			vec![U256::from(2), U256::from(3), U256::from(4)]
+		);
+		assert_eq!(
+			logs.iter().map(|log| log.transaction_log_index.unwrap()).collect::<Vec<_>>(),
+			vec![U256::from(0), U256::from(1), U256::from(2)]
+		);
+		assert!(logs.iter().all(|log| log.transaction_index == Some(U256::from(1))));
+		assert_eq!(logs[1].address, H160::repeat_byte(0x11));
+	}
+
+	#[test]
+	fn pending_nonce_should_count_pooled_transactions() {
+		assert_eq!(pending_nonce(U256::from(3), vec![]), U256::from(3));
+		assert_eq!(pending_nonce(U256::from(3), vec![U256::from(3)]), U256::from(4));
+		assert_eq!(
+			pending_nonce(U256::from(3), vec![U256::from(4), U256::from(3), U256::from(6)]),
+			U256::from(5)
+		);
+	}
+
+	#[test]
+	fn fee_history_should_cover_requested_blocks() {
+		let blocks = [0u64, 4_000_000, 8_000_000].iter()
+			.map(|gas_used| {
+				let mut block = block_with_transfer();
+				block.header.gas_used = U256::from(*gas_used);
+				block
+			})
+			.collect::<Vec<_>>();
+
+		let history = fee_history_build(5, &blocks, U256::from(10), Some(&[25.0, 75.0]));
+
+		assert_eq!(history.oldest_block, U256::from(5));
+		assert_eq!(history.base_fee_per_gas, vec![U256::from(10); 4]);
+		assert_eq!(history.gas_used_ratio, vec![0.0, 0.5, 1.0]);
+		assert_eq!(history.reward, Some(vec![vec![U256::zero(); 2]; 3]));
+
+		let without_rewards = fee_history_build(5, &blocks, U256::from(10), None);
+		let json = serde_json::to_value(&without_rewards).unwrap();
+		assert!(json.get("reward").is_none());
+		assert_eq!(json["oldestBlock"], "0x5");
+	}
+
+	#[test]
+	fn fee_history_should_be_bounded_like_other_block_ranges() {
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default());
+		let eth = eth_api(&client, &pool());
+
+		let history = eth.fee_history(U256::from(4), BlockNumber::Num(0), None).unwrap();
+		assert_eq!(history.oldest_block, U256::zero());
+		let history = eth.fee_history(U256::from(4), BlockNumber::Num(1), None).unwrap();
+		assert_eq!(history.oldest_block, U256::zero());
+
+		let err = eth.fee_history(U256::one(), BlockNumber::Num(2), None).unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+		assert_eq!(err.message, "invalid block range: to block is beyond the best block");
+	}
+
+	fn filter(address: Option<FilterAddress>, topics: Option<Vec<Topic>>) -> Filter {
+		Filter {
+			from_block: None,
+			to_block: None,
+			block_hash: None,
+			address,
+			topics,
+			limit: None,
+		}
+	}
+
+	fn log_with(address: u8, topics: &[u8]) -> Log {
+		Log {
+			address: H160::repeat_byte(address),
+			topics: topics.iter().map(|topic| H256::repeat_byte(*topic)).collect(),
+			data: Bytes(Vec::new()),
+			block_hash: None,
+			block_number: None,
+			transaction_hash: None,
+			transaction_index: None,
+			log_index: None,
+			transaction_log_index: None,
+			log_type: "mined".to_string(),
+			removed: false,
+		}
+	}
+
+	#[test]
+	fn address_filter_should_match_any_listed_address() {
+		let single = filter(Some(VariadicValue::Single(H160::repeat_byte(0x11))), None);
+		assert!(filter_matches(&single, &log_with(0x11, &[0x01])));
+		assert!(!filter_matches(&single, &log_with(0x12, &[0x01])));
+
+		let multiple = filter(Some(VariadicValue::Multiple(vec![
+			H160::repeat_byte(0x11), H160::repeat_byte(0x12),
+		])), None);
+		assert!(filter_matches(&multiple, &log_with(0x12, &[])));
+		assert!(!filter_matches(&multiple, &log_with(0x13, &[])));
+
+		assert!(filter_matches(&filter(None, None), &log_with(0x13, &[])));
+	}
+
+	#[test]
+	fn topic_filter_should_match_by_position() {
+		let first = filter(None, Some(vec![VariadicValue::Single(H256::repeat_byte(0x01))]));
+		assert!(filter_matches(&first, &log_with(0x11, &[0x01, 0x02])));
+		assert!(!filter_matches(&first, &log_with(0x11, &[0x02, 0x01])));
+
+		let second = filter(None, Some(vec![
+			VariadicValue::Null,
+			VariadicValue::Multiple(vec![H256::repeat_byte(0x02), H256::repeat_byte(0x03)]),
+		]));
+		assert!(filter_matches(&second, &log_with(0x11, &[0x09, 0x03])));
+		assert!(!filter_matches(&second, &log_with(0x11, &[0x09, 0x04])));
+		assert!(!filter_matches(&second, &log_with(0x11, &[0x09])));
+	}
+
+	#[test]
+	fn bloom_should_rule_out_blocks_without_matching_logs() {
+		let mut bloom = Bloom::default();
+		bloom.accrue(BloomInput::Raw(&H160::repeat_byte(0x11)[..]));
+		bloom.accrue(BloomInput::Raw(&H256::repeat_byte(0x01)[..]));
+
+		let matching = filter(
+			Some(VariadicValue::Multiple(vec![H160::repeat_byte(0x12), H160::repeat_byte(0x11)])),
+			Some(vec![VariadicValue::Null, VariadicValue::Single(H256::repeat_byte(0x01))]),
+		);
+		assert!(bloom_may_match(&matching, &bloom));
+		assert!(bloom_may_match(&filter(None, None), &bloom));
+
+		let other_address = filter(Some(VariadicValue::Single(H160::repeat_byte(0x12))), None);
+		assert!(!bloom_may_match(&other_address, &bloom));
+		let other_topic = filter(None, Some(vec![VariadicValue::Single(H256::repeat_byte(0x02))]));
+		assert!(!bloom_may_match(&other_topic, &bloom));
+	}
+
+	#[test]
+	fn combined_filter_should_require_address_and_every_topic() {
+		let combined = filter(
+			Some(VariadicValue::Single(H160::repeat_byte(0x11))),
+			Some(vec![
+				VariadicValue::Single(H256::repeat_byte(0x01)),
+				VariadicValue::Single(H256::repeat_byte(0x02)),
+			]),
+		);
+		assert!(filter_matches(&combined, &log_with(0x11, &[0x01, 0x02, 0x03])));
+		assert!(!filter_matches(&combined, &log_with(0x12, &[0x01, 0x02])));
+		assert!(!filter_matches(&combined, &log_with(0x11, &[0x01, 0x03])));
+	}
+
+	fn signed_transfer(secret: &secp256k1::SecretKey, nonce: u64) -> EthereumTransaction {
+		let chain_id = 42;
+		let mut transaction = block_with_transfer().transactions[0].clone();
+		transaction.nonce = U256::from(nonce);
+		let hash = transaction.message_hash(Some(chain_id));
+		let (signature, recovery_id) = secp256k1::sign(
+			&secp256k1::Message::parse(hash.as_fixed_bytes()),
+			secret,
+		);
+		let signature = signature.serialize();
+		transaction.signature = ethereum::TransactionSignature::new(
+			chain_id * 2 + 35 + recovery_id.serialize() as u64,
+			H256::from_slice(&signature[0..32]),
+			H256::from_slice(&signature[32..64]),
+		).unwrap();
+		transaction
+	}
+
+	#[test]
+	fn pending_transactions_should_recover_senders() {
+		let secret = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let transactions = vec![signed_transfer(&secret, 0), signed_transfer(&secret, 1)];
+
+		let pending = pending_transactions_build(transactions.clone());
+
+		assert_eq!(pending.len(), 2);
+		for (built, transaction) in pending.iter().zip(transactions.iter()) {
+			assert_eq!(built.from, sender(&secret));
+			assert_eq!(built.nonce, transaction.nonce);
+			assert_eq!(
+				built.hash,
+				H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice())
+			);
+			assert_eq!(built.block_hash, None);
+			assert_eq!(built.block_number, None);
+		}
+	}
+
+	#[test]
+	fn balance_and_nonce_should_be_answered_at_past_blocks() {
+		let alice = H160::repeat_byte(0xaa);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_account(alice, 0, 100));
+		client.push_block(MockState::default().with_account(alice, 1, 60));
+		let eth = eth_api(&client, &pool());
+
+		assert_eq!(eth.balance(alice, Some(BlockNumber::Num(1))), Ok(U256::from(100)));
+		assert_eq!(eth.balance(alice, Some(BlockNumber::Latest)), Ok(U256::from(60)));
+		assert_eq!(eth.balance(alice, Some(BlockNumber::Earliest)), Ok(U256::from(100)));
+		assert_eq!(eth.transaction_count(alice, Some(BlockNumber::Num(1))), Ok(U256::zero()));
+		assert_eq!(eth.transaction_count(alice, None), Ok(U256::one()));
+
+		let err = eth.balance(alice, Some(BlockNumber::Num(3))).unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+	}
+
+	#[test]
+	fn state_queries_should_fail_beyond_the_best_block() {
+		let contract = H160::repeat_byte(0x11);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_code(contract, vec![0x01, 0x02]));
+		let eth = eth_api(&client, &pool());
+		let unknown = || Some(BlockNumber::Num(2));
+
+		assert_eq!(eth.code_at(contract, Some(BlockNumber::Num(1))), Ok(Bytes(vec![0x01, 0x02])));
+		assert_eq!(eth.code_size(contract, None), Ok(U256::from(2)));
+		assert_eq!(eth.code_at(contract, unknown()).unwrap_err().code, ErrorCode::InvalidParams);
+		assert_eq!(eth.code_size(contract, unknown()).unwrap_err().code, ErrorCode::InvalidParams);
+		assert_eq!(
+			eth.storage_at(contract, U256::zero(), unknown()).unwrap_err().code,
+			ErrorCode::InvalidParams,
+		);
+		assert_eq!(
+			eth.substrate_account(contract, unknown()).unwrap_err().code,
+			ErrorCode::InvalidParams,
+		);
+	}
+
+	#[test]
+	fn omitted_gas_price_should_come_from_the_executed_block() {
+		let contract = H160::repeat_byte(0x11);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_gas_price(1).with_code(contract, vec![0x01]));
+		client.push_block(MockState::default().with_gas_price(2).with_code(contract, vec![0x02]));
+		let eth = eth_api(&client, &pool());
+		let request = || CallRequest { to: Some(contract), ..Default::default() };
+		// The mock runtime returns the gas price it ran with, followed by the callee's code.
+		let output = |gas_price: u8, code: u8| {
+			let mut output = vec![0u8; 32];
+			output[31] = gas_price;
+			output.push(code);
+			Bytes(output)
+		};
+
+		assert_eq!(eth.call(request(), Some(BlockNumber::Num(1))), Ok(output(1, 0x01)));
+		assert_eq!(eth.call(request(), Some(BlockNumber::Latest)), Ok(output(2, 0x02)));
+		assert_eq!(eth.call(request(), Some(BlockNumber::Pending)), Ok(output(2, 0x02)));
+		for number in vec![BlockNumber::Num(1), BlockNumber::Latest, BlockNumber::Pending] {
+			assert_eq!(eth.estimate_gas(request(), Some(number)), Ok(U256::from(21_000)));
+		}
+	}
+
+	#[test]
+	fn explicit_gas_price_should_be_used_at_any_block() {
+		let contract = H160::repeat_byte(0x11);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_gas_price(1).with_code(contract, vec![0x01]));
+		let eth = eth_api(&client, &pool());
+		let request = CallRequest {
+			to: Some(contract),
+			gas_price: Some(U256::from(7)),
+			..Default::default()
+		};
+
+		let output = eth.call(request, Some(BlockNumber::Num(1))).unwrap();
+
+		assert_eq!(U256::from_big_endian(&output.0[..32]), U256::from(7));
+		assert_eq!(output.0[32..].to_vec(), vec![0x01]);
+	}
+
+	fn eth_handler(register_unsupported_methods: bool) -> jsonrpc_core::IoHandler {
+		let client = MockClient::new(MockState::default());
+		let eth = EthApiServer::to_delegate(eth_api(&client, &pool()));
+		let mut io = jsonrpc_core::IoHandler::default();
+		if register_unsupported_methods {
+			io.extend_with(eth);
+		} else {
+			io.extend_with(without_unsupported_methods(eth));
+		}
+		io
+	}
+
+	fn request(io: &jsonrpc_core::IoHandler, method: &str) -> serde_json::Value {
+		request_with_params(io, method, "[]")
+	}
+
+	fn request_with_params(io: &jsonrpc_core::IoHandler, method: &str, params: &str) -> serde_json::Value {
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":{},"id":1}}"#,
+			method,
+			params,
+		);
+		serde_json::from_str(&io.handle_request_sync(&request).unwrap()).unwrap()
+	}
+
+	fn sender(secret: &secp256k1::SecretKey) -> H160 {
+		let public = secp256k1::PublicKey::from_secret_key(secret).serialize();
+		H160::from(H256::from_slice(Keccak256::digest(&public[1..]).as_slice()))
+	}
+
+	fn submit(eth: &MockEthApi, transaction: &EthereumTransaction) -> H256 {
+		eth.send_raw_transaction(Bytes(rlp::encode(transaction))).wait().unwrap()
+	}
+
+	#[test]
+	fn unsupported_methods_should_not_be_registered_if_disabled() {
+		let io = eth_handler(false);
+
+		for method in &["eth_getWork", "eth_hashrate", "eth_compileSolidity", "eth_getProof"] {
+			assert_eq!(request(&io, method)["error"]["message"], "Method not found");
+		}
+		assert_eq!(request(&io, "eth_chainId")["result"], "0x2a");
+	}
+
+	#[test]
+	fn unsupported_methods_should_be_registered_by_default() {
+		let io = eth_handler(true);
+
+		assert_eq!(request(&io, "eth_getWork")["error"]["message"], "mining not supported");
+		assert_eq!(request(&io, "eth_hashrate")["result"], "0x0");
+		assert_eq!(request(&io, "eth_chainId")["result"], "0x2a");
+	}
+
+	#[test]
+	fn timed_out_calls_should_keep_their_thread_until_they_complete() {
+		let contract = H160::repeat_byte(0x11);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState { call_delay: Some(Duration::from_millis(500)), ..Default::default() });
+		client.push_block(MockState::default().with_code(contract, vec![0x01]));
+		let eth = eth_api(&client, &pool()).with_execution_timeout(Duration::from_millis(50), 1);
+		let request = || CallRequest { to: Some(contract), ..Default::default() };
+
+		let err = eth.call(request(), Some(BlockNumber::Num(1))).unwrap_err();
+		assert_eq!(err.message, "execution timed out");
+		let err = eth.call(request(), Some(BlockNumber::Latest)).unwrap_err();
+		assert_eq!(err.message, "server busy");
+
+		thread::sleep(Duration::from_millis(600));
+		let output = eth.call(request(), Some(BlockNumber::Latest)).unwrap();
+		assert_eq!(output.0[32..].to_vec(), vec![0x01]);
+	}
+
+	#[test]
+	fn pending_nonce_should_count_the_senders_pooled_transactions() {
+		let alice = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let bob = secp256k1::SecretKey::parse(&[0x43; 32]).unwrap();
+		let client = MockClient::new(MockState::default());
+		let eth = eth_api(&client, &pool());
+		submit(&eth, &signed_transfer(&alice, 0));
+		submit(&eth, &signed_transfer(&alice, 1));
+		submit(&eth, &signed_transfer(&bob, 2));
+		let mut io = jsonrpc_core::IoHandler::default();
+		io.extend_with(EthApiServer::to_delegate(eth));
+		let transaction_count = |address: H160, number: &str| request_with_params(
+			&io,
+			"eth_getTransactionCount",
+			&format!(r#"["{:?}","{}"]"#, address, number),
+		)["result"].clone();
+
+		assert_eq!(transaction_count(sender(&alice), "pending"), "0x2");
+		assert_eq!(transaction_count(sender(&alice), "latest"), "0x0");
+		// Bob's pooled transaction follows a nonce gap, so it does not count.
+		assert_eq!(transaction_count(sender(&bob), "pending"), "0x0");
+	}
+
+	#[test]
+	fn sender_cache_should_only_recover_new_pooled_transactions() {
+		let secret = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let first = signed_transfer(&secret, 0);
+		let second = signed_transfer(&secret, 1);
+		let cache = SenderCache::default();
+
+		assert_eq!(cache.senders(&[first.clone()]), vec![Some(sender(&secret))]);
+		// A cached sender is reused as is, without recovering it again.
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&first)).as_slice());
+		cache.0.lock().unwrap().insert(hash, None);
+		assert_eq!(cache.senders(&[first.clone(), second.clone()]), vec![None, Some(sender(&secret))]);
+		// Transactions no longer pooled are forgotten.
+		assert_eq!(cache.senders(&[second]), vec![Some(sender(&secret))]);
+		assert_eq!(cache.0.lock().unwrap().len(), 1);
+	}
+
+	#[test]
+	fn txpool_content_should_queue_transactions_after_a_nonce_gap() {
+		let secret = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let client = MockClient::new(MockState::default());
+		let pool = pool();
+		let eth = eth_api(&client, &pool);
+		submit(&eth, &signed_transfer(&secret, 2));
+
+		let content = eth.content().unwrap();
+		assert!(content.pending.is_empty());
+		assert_eq!(content.queued[&sender(&secret)].keys().collect::<Vec<_>>(), vec![&U256::from(2)]);
+		assert_eq!(eth.status(), Ok(TxPoolResult { pending: U256::zero(), queued: U256::one() }));
+
+		submit(&eth, &signed_transfer(&secret, 0));
+
+		let content = eth.content().unwrap();
+		assert_eq!(content.pending[&sender(&secret)].len(), 2);
+		assert!(content.queued.is_empty());
+	}
+
+	#[test]
+	fn version_4_runtime_should_answer_statuses_in_the_legacy_layout() {
+		let block = block_with_transfer();
+		let status = transfer_status(&block);
+		let client = MockClient::new(MockState::default());
+		client.push_block(
+			MockState { api_version: 4, ..Default::default() }
+				.with_block(block.clone(), vec![status.clone()])
+		);
+		let eth = eth_api(&client, &pool());
+		let index = || serde_json::from_str::<Index>(r#""0x0""#).unwrap();
+
+		let receipt = eth.transaction_receipt(status.transaction_hash).unwrap().unwrap();
+		assert_eq!(receipt.transaction_hash, Some(status.transaction_hash));
+		assert_eq!(receipt.from, Some(status.from));
+		assert_eq!(receipt.to, status.to);
+		// Runtimes before version 5 do not track gas usage.
+		assert_eq!(receipt.gas_used, Some(U256::zero()));
+		assert_eq!(receipt.cumulative_gas_used, U256::zero());
+
+		let by_hash = eth.transaction_by_hash(status.transaction_hash).unwrap().unwrap();
+		let by_number = eth.transaction_by_block_number_and_index(BlockNumber::Num(7), index())
+			.unwrap()
+			.unwrap();
+		let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+		let by_block_hash = eth.transaction_by_block_hash_and_index(block_hash, index())
+			.unwrap()
+			.unwrap();
+		for transaction in &[by_hash, by_number, by_block_hash] {
+			assert_eq!(transaction.hash, status.transaction_hash);
+			assert_eq!(transaction.from, status.from);
+			assert_eq!(transaction.block_hash, Some(block_hash));
+		}
+	}
+
+	#[test]
+	fn current_runtime_should_answer_statuses_with_gas_used() {
+		let block = block_with_transfer();
+		let mut status = transfer_status(&block);
+		status.logs_bloom = Bloom::repeat_byte(0x01);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_block(block, vec![status.clone()]));
+		let eth = eth_api(&client, &pool());
+
+		let receipt = eth.transaction_receipt(status.transaction_hash).unwrap().unwrap();
+		assert_eq!(receipt.gas_used, Some(U256::from(21_000)));
+		assert_eq!(receipt.cumulative_gas_used, U256::from(21_000));
+		assert_eq!(receipt.logs_bloom, status.logs_bloom);
+	}
+
+	#[test]
+	fn contract_creation_should_be_executed_up_to_the_configured_init_code_size() {
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState { api_version: 5, ..Default::default() });
+		client.push_block(MockState::default());
+		let eth = eth_api(&client, &pool()).with_max_init_code_size(64);
+		let create = |size| CallRequest { data: Some(Bytes(vec![0x60; size])), ..Default::default() };
+
+		// The mock runtime deploys the init code itself, charging two gas per word of it.
+		assert_eq!(eth.call(create(64), None), Ok(Bytes(vec![0x60; 64])));
+		assert_eq!(eth.estimate_gas(create(64), None), Ok(U256::from(53_004)));
+
+		let err = eth.estimate_gas(create(65), None).unwrap_err();
+		assert_eq!(err.code, ErrorCode::InvalidParams);
+		assert_eq!(err.message, "max initcode size exceeded: 65 > 64");
+
+		let err = eth.estimate_gas(create(64), Some(BlockNumber::Num(1))).unwrap_err();
+		assert_eq!(err.code, ErrorCode::MethodNotFound);
+		assert_eq!(err.message, "method create not supported by runtime");
+	}
+
+	#[test]
+	fn archive_nodes_should_read_pruned_statuses_at_the_block_that_stored_them() {
+		let mut block = block_with_transfer();
+		block.header.number = U256::one();
+		let status = transfer_status(&block);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_block(block.clone(), vec![status.clone()]));
+		// The runtime pruned the status at the next block.
+		client.push_block(MockState::default().with_block(block, Vec::new()));
+		let index = || serde_json::from_str::<Index>(r#""0x0""#).unwrap();
+		let pruned = eth_api(&client, &pool());
+		let archive = eth_api(&client, &pool()).with_archive_state();
+
+		assert_eq!(pruned.transaction_by_block_number_and_index(BlockNumber::Num(1), index()), Ok(None));
+		let transaction = archive.transaction_by_block_number_and_index(BlockNumber::Num(1), index())
+			.unwrap()
+			.unwrap();
+		assert_eq!(transaction.hash, status.transaction_hash);
+		assert_eq!(transaction.from, status.from);
+
+		match archive.block_by_number(BlockNumber::Num(1), true).unwrap().unwrap().inner.transactions {
+			BlockTransactions::Full(transactions) => assert_eq!(transactions[0].from, status.from),
+			BlockTransactions::Hashes(_) => panic!("full transactions requested"),
+		}
+		match pruned.block_by_number(BlockNumber::Num(1), false).unwrap().unwrap().inner.transactions {
+			BlockTransactions::Hashes(hashes) => assert_eq!(hashes, vec![status.transaction_hash]),
+			BlockTransactions::Full(_) => panic!("transaction hashes requested"),
+		}
+	}
+
+	#[test]
+	fn archive_nodes_should_find_pruned_transactions_by_hash() {
+		let mut block = block_with_transfer();
+		block.header.number = U256::one();
+		let status = transfer_status(&block);
+		let client = MockClient::new(MockState::default());
+		client.push_block(MockState::default().with_block(block.clone(), vec![status.clone()]));
+		// The runtime keeps the statuses of a single block, so block 2 pruned those of block 1.
+		client.push_block(MockState { retention: 1, ..MockState::default().with_block(block, Vec::new()) });
+		let pruned = eth_api(&client, &pool());
+		let archive = eth_api(&client, &pool()).with_archive_state();
+
+		assert_eq!(pruned.transaction_by_hash(status.transaction_hash), Ok(None));
+		assert!(pruned.transaction_receipt(status.transaction_hash).unwrap().is_none());
+		let transaction = archive.transaction_by_hash(status.transaction_hash).unwrap().unwrap();
+		assert_eq!(transaction.from, status.from);
+		assert_eq!(transaction.block_number, Some(U256::one()));
+		let receipt = archive.transaction_receipt(status.transaction_hash).unwrap().unwrap();
+		assert_eq!(receipt.transaction_hash, Some(status.transaction_hash));
+		assert_eq!(receipt.gas_used, Some(status.gas_used));
+	}
+
+	#[test]
+	fn logs_should_be_bound_by_the_runtime_retention_unless_archived() {
+		let client = MockClient::new(MockState::default());
+		for _ in 0..5 {
+			client.push_block(MockState { retention: 2, ..Default::default() });
+		}
+		let from = |number| Filter { from_block: Some(BlockNumber::Num(number)), ..filter(None, None) };
+		let pruned = eth_api(&client, &pool());
+
+		// At block 5, the runtime has pruned the statuses of blocks up to 3.
+		let err = pruned.logs(from(3)).wait().unwrap_err();
+		assert_eq!(err.message, "requested range is older than the pruning window");
+		assert_eq!(pruned.logs(from(4)).wait(), Ok(Vec::new()));
+
+		let archive = eth_api(&client, &pool()).with_archive_state();
+		assert_eq!(archive.logs(from(1)).wait(), Ok(Vec::new()));
+	}
+
+	/// Externalities of a template runtime chain whose genesis funds `address`.
+	fn runtime_ext(address: H160) -> sp_io::TestExternalities {
+		use frontier_template_runtime::Runtime;
+
+		let mut storage = frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap();
+		let mut accounts = BTreeMap::new();
+		accounts.insert(address, pallet_evm::GenesisAccount {
+			nonce: U256::zero(),
+			balance: U256::from(1_000_000_000u64),
+			storage: BTreeMap::new(),
+			code: Vec::new(),
+		});
+		pallet_evm::GenesisConfig { accounts }
+			.assimilate_storage::<Runtime>(&mut storage)
+			.unwrap();
+		storage.into()
+	}
+
+	#[test]
+	fn sent_raw_transaction_should_be_found_once_mined() {
+		use frame_support::{assert_ok, traits::OnFinalize};
+		use frontier_template_runtime::{Ethereum, Origin, System};
+
+		let secret = secp256k1::SecretKey::parse(&[0x42; 32]).unwrap();
+		let transaction = signed_transfer(&secret, 0);
+		let hash = H256::from_slice(Keccak256::digest(&rlp::encode(&transaction)).as_slice());
+		let client = MockClient::new(MockState::default());
+		let pool = pool();
+		let mut io = jsonrpc_core::IoHandler::default();
+		io.extend_with(EthApiServer::to_delegate(eth_api(&client, &pool)));
+
+		let raw = serde_json::json!([Bytes(rlp::encode(&transaction))]).to_string();
+		let sent = request_with_params(&io, "eth_sendRawTransaction", &raw);
+		assert_eq!(sent["result"], serde_json::json!(hash));
+		assert_eq!(pool.status().ready, 1);
+
+		// Mine the pooled transaction in block 1 of a template runtime chain, and expose
+		// the block and status the pallet stored to the RPC.
+		let (block, status) = runtime_ext(sender(&secret)).execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(Ethereum::transact(Origin::none(), transaction.clone()));
+			Ethereum::on_finalize(1);
+			let (_, block, status) = Ethereum::transaction_by_hash(hash).unwrap();
+			(block, status)
+		});
+		client.push_block(MockState::default().with_block(block.clone(), vec![status.clone()]));
+		let block_hash = H256::from_slice(Keccak256::digest(&rlp::encode(&block.header)).as_slice());
+
+		let params = serde_json::json!([hash]).to_string();
+		let found = request_with_params(&io, "eth_getTransactionByHash", &params)["result"].clone();
+		let receipt = request_with_params(&io, "eth_getTransactionReceipt", &params)["result"].clone();
+		assert_eq!(found["hash"], serde_json::json!(hash));
+		assert_eq!(receipt["transactionHash"], serde_json::json!(hash));
+		for key in &["blockHash", "blockNumber", "transactionIndex", "from"] {
+			assert_eq!(found[key], receipt[key], "{} differs", key);
+		}
+		assert_eq!(receipt["blockHash"], serde_json::json!(block_hash));
+		assert_eq!(receipt["blockNumber"], "0x1");
+		assert_eq!(receipt["transactionIndex"], "0x0");
+		assert_eq!(receipt["from"], serde_json::json!(sender(&secret)));
+		assert_eq!(receipt["to"], serde_json::json!(H160::repeat_byte(0x11)));
+		// A plain transfer uses exactly the intrinsic gas and emits no logs.
+		assert_eq!(receipt["gasUsed"], "0x5208");
+		assert_eq!(receipt["cumulativeGasUsed"], "0x5208");
+		assert_eq!(receipt["logs"], serde_json::json!([]));
+		assert_eq!(receipt["logsBloom"], serde_json::json!(Bloom::default()));
+		assert_eq!(block.header.gas_used, U256::from(21_000));
 	}
 }
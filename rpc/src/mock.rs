@@ -0,0 +1,808 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Test utilities: a client whose runtime answers `EthereumRuntimeApi` calls from the
+//! mock state of the queried block, and a transaction pool carrying Ethereum transactions.
+
+use std::{collections::{BTreeMap, HashMap}, sync::Arc, thread, time::Duration};
+use codec::{Decode, Encode};
+use ethereum::{Block as EthereumBlock, Transaction as EthereumTransaction};
+use ethereum_types::{H160, H256, U256};
+use parking_lot::RwLock;
+use sha3::{Keccak256, Digest};
+use sp_api::{
+	ApiErrorExt, ApiExt, ApiRef, BlockId, ChangesTrieState, Core, ExecutionContext, HashFor,
+	InMemoryBackend, NativeOrEncoded, NumberFor, ProvideRuntimeApi, RuntimeApiInfo, RuntimeVersion,
+	StorageChanges, StorageProof,
+};
+use sp_blockchain::{CachedHeaderMetadata, HeaderMetadata};
+use sp_consensus::BlockOrigin;
+use sp_runtime::{AccountId32, traits::{Block as BlockT, Header as _}};
+use sp_storage::StorageKey;
+use sp_utils::mpsc::{TracingUnboundedSender, tracing_unbounded};
+use sc_client_api::{
+	BlockImportNotification, BlockchainEvents, FinalityNotifications, ImportNotifications,
+	StorageEventStream,
+};
+use sc_transaction_pool::BasicPool;
+use substrate_test_runtime_client::{AccountKeyring, runtime::{Block, Extrinsic, Header, Transfer}};
+use substrate_test_runtime_transaction_pool::TestApi;
+use frontier_rpc_primitives::{
+	EthereumRuntimeApi, ConvertTransaction, ExecutionError, LegacyTransactionStatus,
+	TransactionStatus, MAX_BLOCKS_IN_RANGE,
+};
+
+use crate::{EthApi, SubmissionConfig, SyncStatusProvider};
+
+pub type Pool = BasicPool<TestApi, Block>;
+pub type MockEthApi = EthApi<Block, MockClient, MockClient, Pool, MockClient>;
+
+fn keccak(bytes: &[u8]) -> H256 {
+	H256::from_slice(Keccak256::digest(bytes).as_slice())
+}
+
+/// Ethereum state the mock runtime exposes at one Substrate block.
+#[derive(Clone)]
+pub struct MockState {
+	/// Version of `EthereumRuntimeApi` the runtime implements.
+	pub api_version: u32,
+	pub chain_id: u64,
+	pub gas_price: U256,
+	pub accounts: BTreeMap<H160, pallet_evm::Account>,
+	pub code: BTreeMap<H160, Vec<u8>>,
+	/// Ethereum blocks stored so far, with the statuses of their transactions.
+	pub blocks: Vec<(EthereumBlock, Vec<TransactionStatus>)>,
+	/// Time every `call` takes to execute.
+	pub call_delay: Option<Duration>,
+	/// Number of recent blocks whose transaction statuses the runtime keeps.
+	pub retention: u32,
+}
+
+impl Default for MockState {
+	fn default() -> Self {
+		Self {
+			api_version: <dyn EthereumRuntimeApi<Block> as RuntimeApiInfo>::VERSION,
+			chain_id: 42,
+			gas_price: U256::one(),
+			accounts: BTreeMap::new(),
+			code: BTreeMap::new(),
+			blocks: Vec::new(),
+			call_delay: None,
+			retention: 0,
+		}
+	}
+}
+
+impl MockState {
+	pub fn with_account(mut self, address: H160, nonce: u64, balance: u64) -> Self {
+		self.accounts.insert(address, pallet_evm::Account {
+			nonce: U256::from(nonce),
+			balance: U256::from(balance),
+		});
+		self
+	}
+
+	pub fn with_code(mut self, address: H160, code: Vec<u8>) -> Self {
+		self.code.insert(address, code);
+		self
+	}
+
+	pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+		self.gas_price = U256::from(gas_price);
+		self
+	}
+
+	pub fn with_block(mut self, block: EthereumBlock, statuses: Vec<TransactionStatus>) -> Self {
+		self.blocks.push((block, statuses));
+		self
+	}
+
+	fn block_by_number(&self, number: u32) -> Option<&(EthereumBlock, Vec<TransactionStatus>)> {
+		self.blocks.iter().find(|(block, _)| block.header.number == U256::from(number))
+	}
+
+	fn block_by_hash(&self, hash: H256) -> Option<&(EthereumBlock, Vec<TransactionStatus>)> {
+		self.blocks.iter().find(|(block, _)| keccak(&rlp::encode(&block.header)) == hash)
+	}
+
+	fn transaction_by_hash(&self, hash: H256) -> Option<(EthereumTransaction, EthereumBlock, TransactionStatus)> {
+		self.blocks.iter().find_map(|(block, statuses)| {
+			statuses.iter()
+				.find(|status| status.transaction_hash == hash)
+				.map(|status| (
+					block.transactions[status.transaction_index as usize].clone(),
+					block.clone(),
+					status.clone(),
+				))
+		})
+	}
+
+	fn transaction_at(
+		(block, statuses): &(EthereumBlock, Vec<TransactionStatus>),
+		index: u32,
+	) -> Option<(EthereumTransaction, EthereumBlock, TransactionStatus)> {
+		Some((
+			block.transactions.get(index as usize)?.clone(),
+			block.clone(),
+			statuses.get(index as usize)?.clone(),
+		))
+	}
+}
+
+struct Chain {
+	blocks: HashMap<H256, (Header, MockState)>,
+	best: H256,
+}
+
+impl Chain {
+	/// Block `number` of the chain ending at the best block.
+	fn canonical(&self, number: u64) -> Option<&(Header, MockState)> {
+		let mut current = self.blocks.get(&self.best)?;
+		while *current.0.number() > number {
+			current = self.blocks.get(current.0.parent_hash())?;
+		}
+		Some(current).filter(|(header, _)| *header.number() == number)
+	}
+}
+
+struct Inner {
+	chain: RwLock<Chain>,
+	/// Ethereum transactions submitted to the pool, indexed by the amount of the test
+	/// runtime transfer carrying them.
+	pooled: RwLock<Vec<EthereumTransaction>>,
+	import_sinks: RwLock<Vec<TracingUnboundedSender<BlockImportNotification<Block>>>>,
+}
+
+/// Client, select chain and transaction converter of the tests, sharing one chain.
+#[derive(Clone)]
+pub struct MockClient(Arc<Inner>);
+
+impl MockClient {
+	/// Start a chain whose genesis block exposes `genesis`.
+	pub fn new(genesis: MockState) -> Self {
+		let header = Header::new(0, H256::zero(), H256::zero(), H256::zero(), Default::default());
+		let best = header.hash();
+		let mut blocks = HashMap::new();
+		blocks.insert(best, (header, genesis));
+		MockClient(Arc::new(Inner {
+			chain: RwLock::new(Chain { blocks, best }),
+			pooled: RwLock::new(Vec::new()),
+			import_sinks: RwLock::new(Vec::new()),
+		}))
+	}
+
+	/// Import a block exposing `state` on top of the best block, making it the new best.
+	pub fn push_block(&self, state: MockState) -> Header {
+		let best = self.0.chain.read().best;
+		self.import(best, state, true)
+	}
+
+	/// Import a block exposing `state` on top of `parent`, leaving the best block unchanged.
+	pub fn push_fork(&self, parent: H256, state: MockState) -> Header {
+		self.import(parent, state, false)
+	}
+
+	/// Import a block exposing `state` on top of `parent`, making it the new best even if
+	/// this retracts blocks of the previous best chain.
+	pub fn push_reorg(&self, parent: H256, state: MockState) -> Header {
+		self.import(parent, state, true)
+	}
+
+	/// Import a block and notify the import notification streams of it.
+	fn import(&self, parent: H256, state: MockState, is_new_best: bool) -> Header {
+		let header = {
+			let mut chain = self.0.chain.write();
+			let parent = chain.blocks[&parent].0.clone();
+			let header = Header::new(
+				parent.number() + 1,
+				H256::zero(),
+				H256::from_low_u64_be(chain.blocks.len() as u64),
+				parent.hash(),
+				Default::default(),
+			);
+			if is_new_best {
+				chain.best = header.hash();
+			}
+			chain.blocks.insert(header.hash(), (header.clone(), state));
+			header
+		};
+		let notification = BlockImportNotification {
+			hash: header.hash(),
+			origin: BlockOrigin::Own,
+			header: header.clone(),
+			is_new_best,
+			retracted: Vec::new(),
+		};
+		self.0.import_sinks.write().retain(|sink| sink.unbounded_send(notification.clone()).is_ok());
+		header
+	}
+
+	fn pooled(&self) -> Vec<EthereumTransaction> {
+		self.0.pooled.read().clone()
+	}
+
+	fn state_at(&self, at: &BlockId<Block>) -> Result<MockState, String> {
+		let chain = self.0.chain.read();
+		match at {
+			BlockId::Hash(hash) => chain.blocks.get(hash),
+			BlockId::Number(number) => chain.canonical(*number),
+		}.map(|(_, state)| state.clone()).ok_or_else(|| format!("unknown block {:?}", at))
+	}
+}
+
+impl ProvideRuntimeApi<Block> for MockClient {
+	type Api = MockRuntimeApi;
+
+	fn runtime_api<'a>(&'a self) -> ApiRef<'a, Self::Api> {
+		MockRuntimeApi(self.clone()).into()
+	}
+}
+
+impl sp_consensus::SelectChain<Block> for MockClient {
+	fn leaves(&self) -> Result<Vec<H256>, sp_consensus::Error> {
+		Ok(vec![self.0.chain.read().best])
+	}
+
+	fn best_chain(&self) -> Result<Header, sp_consensus::Error> {
+		let chain = self.0.chain.read();
+		Ok(chain.blocks[&chain.best].0.clone())
+	}
+}
+
+impl HeaderMetadata<Block> for MockClient {
+	type Error = sp_blockchain::Error;
+
+	fn header_metadata(&self, hash: H256) -> sp_blockchain::Result<CachedHeaderMetadata<Block>> {
+		self.0.chain.read().blocks.get(&hash)
+			.map(|(header, _)| CachedHeaderMetadata::from(header))
+			.ok_or_else(|| sp_blockchain::Error::UnknownBlock(format!("{:?}", hash)))
+	}
+
+	fn insert_header_metadata(&self, _: H256, _: CachedHeaderMetadata<Block>) {}
+
+	fn remove_header_metadata(&self, _: H256) {}
+}
+
+/// Only block imports are notified; the other streams end right away.
+impl BlockchainEvents<Block> for MockClient {
+	fn import_notification_stream(&self) -> ImportNotifications<Block> {
+		let (sink, stream) = tracing_unbounded("mpsc_import_notification_stream");
+		self.0.import_sinks.write().push(sink);
+		stream
+	}
+
+	fn finality_notification_stream(&self) -> FinalityNotifications<Block> {
+		tracing_unbounded("mpsc_finality_notification_stream").1
+	}
+
+	fn storage_changes_notification_stream(
+		&self,
+		_: Option<&[StorageKey]>,
+		_: Option<&[(StorageKey, Option<Vec<StorageKey>>)]>,
+	) -> sp_blockchain::Result<StorageEventStream<H256>> {
+		Ok(tracing_unbounded("mpsc_storage_notification_items").1)
+	}
+}
+
+/// Carries an Ethereum transaction in a test runtime transfer with the same nonce, so that
+/// the pool tells ready and future transactions apart by their Ethereum nonce.
+impl ConvertTransaction<Extrinsic> for MockClient {
+	fn convert_transaction(&self, transaction: EthereumTransaction) -> Extrinsic {
+		let mut pooled = self.0.pooled.write();
+		let transfer = Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: pooled.len() as u64,
+			nonce: transaction.nonce.low_u64(),
+		};
+		pooled.push(transaction);
+		transfer.into_signed_tx()
+	}
+}
+
+/// Runtime of a `MockClient`. Runtimes only provide the methods their API version has,
+/// and answer deprecated methods in their encoded form, as real runtimes do.
+pub struct MockRuntimeApi(MockClient);
+
+impl MockRuntimeApi {
+	/// Answer a call to a method added in version `since` by applying `f` to its
+	/// parameters and the state at `at`.
+	fn answer<P, R>(
+		&self,
+		at: &BlockId<Block>,
+		since: u32,
+		params: Option<P>,
+		f: impl FnOnce(&MockState, P) -> R,
+	) -> Result<NativeOrEncoded<R>, String> {
+		let state = self.0.state_at(at)?;
+		if state.api_version < since {
+			return Err(format!("runtime at {:?} does not provide this method", at));
+		}
+		let params = params.ok_or_else(|| "deprecated method called natively".to_string())?;
+		Ok(NativeOrEncoded::Native(f(&state, params)))
+	}
+
+	/// Answer a call to a method whose result changed in version `changed_in`. Runtimes
+	/// before that version only answer the deprecated method, whose encoded result `legacy`
+	/// derives from the current one.
+	fn answer_changed<P: Decode, R, L: Encode>(
+		&self,
+		at: &BlockId<Block>,
+		changed_in: u32,
+		params: Option<P>,
+		params_encoded: Vec<u8>,
+		f: impl FnOnce(&MockState, P) -> R,
+		legacy: impl FnOnce(R) -> L,
+	) -> Result<NativeOrEncoded<R>, String> {
+		let state = self.0.state_at(at)?;
+		match params {
+			Some(params) if state.api_version >= changed_in => {
+				Ok(NativeOrEncoded::Native(f(&state, params)))
+			},
+			None if state.api_version < changed_in => {
+				let params = P::decode(&mut &params_encoded[..])
+					.map_err(|_| "invalid parameters".to_string())?;
+				Ok(NativeOrEncoded::Encoded(legacy(f(&state, params)).encode()))
+			},
+			_ => Err(format!("runtime at {:?} answers another version of this method", at)),
+		}
+	}
+}
+
+impl ApiErrorExt for MockRuntimeApi {
+	type Error = String;
+}
+
+impl ApiExt<Block> for MockRuntimeApi {
+	type StateBackend = InMemoryBackend<HashFor<Block>>;
+
+	fn map_api_result<F: FnOnce(&Self) -> Result<R, E>, R, E>(
+		&self,
+		map_call: F,
+	) -> Result<R, E> where Self: Sized {
+		map_call(self)
+	}
+
+	fn has_api<A: RuntimeApiInfo + ?Sized>(&self, at: &BlockId<Block>) -> Result<bool, String> where
+		Self: Sized,
+	{
+		self.has_api_with::<A, _>(at, |version| version == A::VERSION)
+	}
+
+	fn has_api_with<A: RuntimeApiInfo + ?Sized, P: Fn(u32) -> bool>(
+		&self,
+		at: &BlockId<Block>,
+		pred: P,
+	) -> Result<bool, String> where Self: Sized {
+		let state = self.0.state_at(at)?;
+		Ok(A::ID == <dyn EthereumRuntimeApi<Block> as RuntimeApiInfo>::ID && pred(state.api_version))
+	}
+
+	fn record_proof(&mut self) {
+		unimplemented!("Not required for testing!")
+	}
+
+	fn extract_proof(&mut self) -> Option<StorageProof> {
+		unimplemented!("Not required for testing!")
+	}
+
+	fn into_storage_changes(
+		&self,
+		_: &Self::StateBackend,
+		_: Option<&ChangesTrieState<HashFor<Block>, NumberFor<Block>>>,
+		_: H256,
+	) -> Result<StorageChanges<Self::StateBackend, Block>, String> where Self: Sized {
+		unimplemented!("Not required for testing!")
+	}
+}
+
+impl Core<Block> for MockRuntimeApi {
+	fn Core_version_runtime_api_impl(
+		&self,
+		_: &BlockId<Block>,
+		_: ExecutionContext,
+		_: Option<()>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<RuntimeVersion>, String> {
+		unimplemented!("Not required for testing!")
+	}
+
+	fn Core_execute_block_runtime_api_impl(
+		&self,
+		_: &BlockId<Block>,
+		_: ExecutionContext,
+		_: Option<Block>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<()>, String> {
+		unimplemented!("Not required for testing!")
+	}
+
+	fn Core_initialize_block_runtime_api_impl(
+		&self,
+		_: &BlockId<Block>,
+		_: ExecutionContext,
+		_: Option<&<Block as BlockT>::Header>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<()>, String> {
+		unimplemented!("Not required for testing!")
+	}
+}
+
+type CallParams = (H160, H160, Vec<u8>, U256, U256, U256, Option<U256>);
+
+/// Output of a call: the gas price it ran with, followed by the code of the callee.
+fn call_output(state: &MockState, (_, to, _, _, _, gas_price, _): CallParams) -> (Vec<u8>, U256) {
+	if let Some(delay) = state.call_delay {
+		thread::sleep(delay);
+	}
+	let mut output = [0u8; 32];
+	gas_price.to_big_endian(&mut output);
+	let mut output = output.to_vec();
+	output.extend(state.code.get(&to).cloned().unwrap_or_default());
+	(output, U256::from(21_000))
+}
+
+type CreateParams = (H160, Vec<u8>, U256, U256, U256, Option<U256>);
+
+/// Outcome of a contract creation: the init code itself, as the deployed code, and the
+/// creation cost plus two gas per word of init code.
+fn create_output((_, data, _, _, _, _): CreateParams) -> (Vec<u8>, U256) {
+	let words = (data.len() as u64 + 31) / 32;
+	(data, U256::from(53_000 + 2 * words))
+}
+
+type TransactionAndBlock = Option<(EthereumTransaction, EthereumBlock, TransactionStatus)>;
+
+/// Status in the layout of runtimes before version 5.
+fn legacy_status(status: TransactionStatus) -> LegacyTransactionStatus {
+	LegacyTransactionStatus {
+		transaction_hash: status.transaction_hash,
+		transaction_index: status.transaction_index,
+		from: status.from,
+		to: status.to,
+		contract_address: status.contract_address,
+		logs: status.logs,
+		logs_bloom: status.logs_bloom,
+	}
+}
+
+fn legacy_transaction_and_block(
+	found: TransactionAndBlock,
+) -> Option<(EthereumTransaction, EthereumBlock, LegacyTransactionStatus)> {
+	found.map(|(transaction, block, status)| (transaction, block, legacy_status(status)))
+}
+
+impl EthereumRuntimeApi<Block> for MockRuntimeApi {
+	fn EthereumRuntimeApi_chain_id_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<()>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<u64>, String> {
+		self.answer(at, 1, params, |state, ()| state.chain_id)
+	}
+
+	fn EthereumRuntimeApi_account_basic_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H160>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<pallet_evm::Account>, String> {
+		self.answer(at, 1, params, |state, address| {
+			state.accounts.get(&address).cloned().unwrap_or_default()
+		})
+	}
+
+	fn EthereumRuntimeApi_transaction_status_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H256>,
+		params_encoded: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<TransactionStatus>>, String> {
+		self.answer_changed(
+			at,
+			5,
+			params,
+			params_encoded,
+			|state, hash| state.transaction_by_hash(hash).map(|(_, _, status)| status),
+			|status| status.map(legacy_status),
+		)
+	}
+
+	fn EthereumRuntimeApi_gas_price_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<()>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<U256>, String> {
+		self.answer(at, 1, params, |state, ()| state.gas_price)
+	}
+
+	fn EthereumRuntimeApi_account_code_at_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H160>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Vec<u8>>, String> {
+		self.answer(at, 1, params, |state, address| {
+			state.code.get(&address).cloned().unwrap_or_default()
+		})
+	}
+
+	fn EthereumRuntimeApi_account_code_len_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H160>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<u64>, String> {
+		self.answer(at, 2, params, |state, address| {
+			state.code.get(&address).map_or(0, |code| code.len() as u64)
+		})
+	}
+
+	fn EthereumRuntimeApi_author_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<()>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<H160>, String> {
+		self.answer(at, 1, params, |_, ()| H160::zero())
+	}
+
+	fn EthereumRuntimeApi_storage_at_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<(H160, U256)>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<H256>, String> {
+		self.answer(at, 1, params, |_, _| H256::zero())
+	}
+
+	fn EthereumRuntimeApi_substrate_account_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H160>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<AccountId32>>, String> {
+		self.answer(at, 4, params, |_, _| None)
+	}
+
+	fn EthereumRuntimeApi_call_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<CallParams>,
+		params_encoded: Vec<u8>,
+	) -> Result<NativeOrEncoded<Result<(Vec<u8>, U256), ExecutionError>>, String> {
+		let state = self.0.state_at(at)?;
+		match params {
+			Some(params) => Ok(NativeOrEncoded::Native(Ok(call_output(&state, params)))),
+			// Runtimes before version 2 report failed executions as `None`.
+			None => {
+				let params = CallParams::decode(&mut &params_encoded[..])
+					.map_err(|_| "invalid call parameters".to_string())?;
+				Ok(NativeOrEncoded::Encoded(Some(call_output(&state, params)).encode()))
+			},
+		}
+	}
+
+	fn EthereumRuntimeApi_create_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<CreateParams>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Result<(Vec<u8>, U256), ExecutionError>>, String> {
+		self.answer(at, 6, params, |_, params| Ok(create_output(params)))
+	}
+
+	fn EthereumRuntimeApi_block_by_number_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<u32>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<EthereumBlock>>, String> {
+		self.answer(at, 1, params, |state, number| {
+			state.block_by_number(number).map(|(block, _)| block.clone())
+		})
+	}
+
+	fn EthereumRuntimeApi_block_transaction_count_by_number_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<u32>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<U256>>, String> {
+		self.answer(at, 1, params, |state, number| {
+			state.block_by_number(number).map(|(block, _)| U256::from(block.transactions.len()))
+		})
+	}
+
+	fn EthereumRuntimeApi_block_by_hash_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H256>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<EthereumBlock>>, String> {
+		self.answer(at, 1, params, |state, hash| {
+			state.block_by_hash(hash).map(|(block, _)| block.clone())
+		})
+	}
+
+	fn EthereumRuntimeApi_blocks_in_range_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<(u32, u32)>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Vec<EthereumBlock>>, String> {
+		self.answer(at, 3, params, |state, (from, to)| {
+			let to = to.min(from.saturating_add(MAX_BLOCKS_IN_RANGE - 1));
+			(from..=to)
+				.filter_map(|number| state.block_by_number(number).map(|(block, _)| block.clone()))
+				.collect()
+		})
+	}
+
+	fn EthereumRuntimeApi_block_transaction_count_by_hash_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H256>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<U256>>, String> {
+		self.answer(at, 1, params, |state, hash| {
+			state.block_by_hash(hash).map(|(block, _)| U256::from(block.transactions.len()))
+		})
+	}
+
+	fn EthereumRuntimeApi_block_transaction_hashes_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H256>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Option<Vec<H256>>>, String> {
+		// Blocks whose statuses were pruned no longer list their transaction hashes.
+		self.answer(at, 2, params, |state, hash| {
+			state.block_by_hash(hash)
+				.filter(|(block, statuses)| statuses.len() == block.transactions.len())
+				.map(|(_, statuses)| {
+					statuses.iter().map(|status| status.transaction_hash).collect()
+				})
+		})
+	}
+
+	fn EthereumRuntimeApi_transaction_by_hash_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<H256>,
+		params_encoded: Vec<u8>,
+	) -> Result<NativeOrEncoded<TransactionAndBlock>, String> {
+		self.answer_changed(
+			at,
+			5,
+			params,
+			params_encoded,
+			|state, hash| state.transaction_by_hash(hash),
+			legacy_transaction_and_block,
+		)
+	}
+
+	fn EthereumRuntimeApi_transaction_by_block_hash_and_index_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<(H256, u32)>,
+		params_encoded: Vec<u8>,
+	) -> Result<NativeOrEncoded<TransactionAndBlock>, String> {
+		self.answer_changed(
+			at,
+			5,
+			params,
+			params_encoded,
+			|state, (hash, index)| {
+				state.block_by_hash(hash).and_then(|block| MockState::transaction_at(block, index))
+			},
+			legacy_transaction_and_block,
+		)
+	}
+
+	fn EthereumRuntimeApi_transaction_by_block_number_and_index_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<(u32, u32)>,
+		params_encoded: Vec<u8>,
+	) -> Result<NativeOrEncoded<TransactionAndBlock>, String> {
+		self.answer_changed(
+			at,
+			5,
+			params,
+			params_encoded,
+			|state, (number, index)| {
+				state.block_by_number(number).and_then(|block| MockState::transaction_at(block, index))
+			},
+			legacy_transaction_and_block,
+		)
+	}
+
+	fn EthereumRuntimeApi_extrinsic_filter_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<Vec<Extrinsic>>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<Vec<EthereumTransaction>>, String> {
+		let pooled = self.0.pooled();
+		self.answer(at, 2, params, move |_, xts| {
+			xts.iter()
+				.filter_map(|xt| pooled.get(xt.transfer().amount as usize).cloned())
+				.collect()
+		})
+	}
+
+	fn EthereumRuntimeApi_transaction_status_retention_runtime_api_impl(
+		&self,
+		at: &BlockId<Block>,
+		_: ExecutionContext,
+		params: Option<()>,
+		_: Vec<u8>,
+	) -> Result<NativeOrEncoded<u32>, String> {
+		self.answer(at, 7, params, |state, ()| state.retention)
+	}
+}
+
+/// A node that is not syncing.
+pub struct Synced;
+
+impl SyncStatusProvider for Synced {
+	fn is_major_syncing(&self) -> bool {
+		false
+	}
+}
+
+/// A pool validating transactions against the test runtime's account nonces.
+pub fn pool() -> Arc<Pool> {
+	let (pool, _) = BasicPool::new(Default::default(), Arc::new(TestApi::empty()), None);
+	Arc::new(pool)
+}
+
+/// An `EthApi` over `client` and `pool`, with default settings.
+pub fn eth_api(client: &MockClient, pool: &Arc<Pool>) -> MockEthApi {
+	EthApi::new(
+		Arc::new(client.clone()),
+		client.clone(),
+		pool.clone(),
+		client.clone(),
+		false,
+		Arc::new(Synced),
+		SubmissionConfig::default(),
+	)
+}
@@ -0,0 +1,236 @@
+// Copyright 2017-2020 Parity Technologies (UK) Ltd.
+// This file is part of Frontier.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Message and typed data signing with keys from the node keystore.
+
+use ethereum_types::{H160, H256, H520};
+use jsonrpc_core::Result;
+use sha3::{Keccak256, Digest};
+use sp_core::{ecdsa, crypto::KeyTypeId};
+use sc_keystore::KeyStorePtr;
+use frontier_rpc_core::EthSigningApi as EthSigningApiT;
+use frontier_rpc_core::types::{Bytes, TransactionRequest, RichRawTransaction, TypedData};
+use crate::{internal_err, invalid_params_err, eip712};
+
+/// Key type of the ECDSA keys used to sign on behalf of Ethereum accounts.
+pub const ETH_KEY_TYPE: KeyTypeId = KeyTypeId(*b"eth ");
+
+/// Hash a message as `keccak256("\x19Ethereum Signed Message:\n" ‖ len(message) ‖ message)`.
+pub fn personal_message_hash(message: &[u8]) -> H256 {
+	let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+	data.extend_from_slice(message);
+	H256::from_slice(Keccak256::digest(&data).as_slice())
+}
+
+fn public_key_address(public: &secp256k1::PublicKey) -> H160 {
+	let public = public.serialize();
+	H160::from(H256::from_slice(Keccak256::digest(&public[1..]).as_slice()))
+}
+
+/// Secret keys of the Ethereum accounts held in `keystore`.
+fn secret_keys(keystore: &KeyStorePtr) -> Vec<secp256k1::SecretKey> {
+	let keystore = keystore.read();
+	keystore.public_keys_by_type::<ecdsa::Public>(ETH_KEY_TYPE).unwrap_or_default()
+		.into_iter()
+		.filter_map(|public| keystore.key_pair_by_type::<ecdsa::Pair>(&public, ETH_KEY_TYPE).ok())
+		.filter_map(|pair| secp256k1::SecretKey::parse(&pair.seed()).ok())
+		.collect()
+}
+
+/// Addresses of the Ethereum accounts held in `keystore`.
+pub fn keystore_addresses(keystore: &KeyStorePtr) -> Vec<H160> {
+	secret_keys(keystore).iter()
+		.map(|secret| public_key_address(&secp256k1::PublicKey::from_secret_key(secret)))
+		.collect()
+}
+
+pub struct EthSigningApi {
+	keystore: KeyStorePtr,
+}
+
+impl EthSigningApi {
+	pub fn new(keystore: KeyStorePtr) -> Self {
+		Self { keystore }
+	}
+
+	/// Find the keystore key controlling `address`.
+	fn secret_key(&self, address: H160) -> Option<secp256k1::SecretKey> {
+		secret_keys(&self.keystore)
+			.into_iter()
+			.find(|secret| public_key_address(&secp256k1::PublicKey::from_secret_key(secret)) == address)
+	}
+
+	/// Sign `hash` with the key of `address`, returning `r ‖ s ‖ v` with `v` in `{27, 28}`.
+	fn sign_hash(&self, address: H160, hash: H256) -> Result<H520> {
+		let secret = self.secret_key(address)
+			.ok_or_else(|| invalid_params_err(&format!("no key for address {:?} in keystore", address)))?;
+		let (signature, recovery_id) = secp256k1::sign(
+			&secp256k1::Message::parse(hash.as_fixed_bytes()),
+			&secret,
+		);
+
+		let mut result = [0u8; 65];
+		result[0..64].copy_from_slice(&signature.serialize()[..]);
+		result[64] = recovery_id.serialize() + 27;
+		Ok(H520::from(result))
+	}
+}
+
+impl EthSigningApiT for EthSigningApi {
+	fn sign(&self, address: H160, data: Bytes) -> Result<H520> {
+		self.sign_hash(address, personal_message_hash(&data.0))
+	}
+
+	fn sign_typed_data(&self, address: H160, typed_data: TypedData) -> Result<H520> {
+		let hash = eip712::hash_typed_data(&typed_data)
+			.map_err(|message| invalid_params_err(&format!("invalid typed data: {}", message)))?;
+		self.sign_hash(address, hash)
+	}
+
+	fn send_transaction(&self, _: TransactionRequest) -> Result<H256> {
+		Err(internal_err("transaction signing is not supported"))
+	}
+
+	fn sign_transaction(&self, _: TransactionRequest) -> Result<RichRawTransaction> {
+		Err(internal_err("transaction signing is not supported"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_keystore::Store;
+
+	/// The "Cow" account from the EIP-712 example, with key `keccak256("cow")`.
+	const COW_SEED: &str = "0xc85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4";
+
+	fn cow() -> H160 {
+		"cd2a3d9f938e13cd947ec05abc7fe734df8dd826".parse().unwrap()
+	}
+
+	fn signing_api() -> EthSigningApi {
+		let keystore = Store::new_in_memory();
+		keystore.write()
+			.insert_ephemeral_from_seed_by_type::<ecdsa::Pair>(COW_SEED, ETH_KEY_TYPE)
+			.unwrap();
+		EthSigningApi::new(keystore)
+	}
+
+	fn recover(hash: H256, signature: H520) -> H160 {
+		let signature = signature.as_bytes();
+		let mut rs = [0u8; 64];
+		rs.copy_from_slice(&signature[0..64]);
+		let public = secp256k1::recover(
+			&secp256k1::Message::parse(hash.as_fixed_bytes()),
+			&secp256k1::Signature::parse(&rs),
+			&secp256k1::RecoveryId::parse_rpc(signature[64]).unwrap(),
+		).unwrap();
+		public_key_address(&public)
+	}
+
+	fn mail() -> TypedData {
+		serde_json::from_str(r#"{
+			"types": {
+				"EIP712Domain": [
+					{ "name": "name", "type": "string" },
+					{ "name": "version", "type": "string" },
+					{ "name": "chainId", "type": "uint256" },
+					{ "name": "verifyingContract", "type": "address" }
+				],
+				"Person": [
+					{ "name": "name", "type": "string" },
+					{ "name": "wallet", "type": "address" }
+				],
+				"Mail": [
+					{ "name": "from", "type": "Person" },
+					{ "name": "to", "type": "Person" },
+					{ "name": "contents", "type": "string" }
+				]
+			},
+			"primaryType": "Mail",
+			"domain": {
+				"name": "Ether Mail",
+				"version": "1",
+				"chainId": 1,
+				"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+			},
+			"message": {
+				"from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+				"to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+				"contents": "Hello, Bob!"
+			}
+		}"#).unwrap()
+	}
+
+	#[test]
+	fn signed_message_should_recover_to_signer() {
+		let api = signing_api();
+		let message = b"Hello, Frontier!".to_vec();
+
+		let signature = api.sign(cow(), Bytes(message.clone())).unwrap();
+
+		assert!(signature[64] == 27 || signature[64] == 28);
+		assert_eq!(recover(personal_message_hash(&message), signature), cow());
+	}
+
+	#[test]
+	fn keystore_addresses_should_list_every_account() {
+		let keystore = Store::new_in_memory();
+		keystore.write()
+			.insert_ephemeral_from_seed_by_type::<ecdsa::Pair>(COW_SEED, ETH_KEY_TYPE)
+			.unwrap();
+		let other = keystore.write()
+			.insert_ephemeral_from_seed_by_type::<ecdsa::Pair>("//Alice", ETH_KEY_TYPE)
+			.unwrap();
+		let other_secret = secp256k1::SecretKey::parse(&other.seed()).unwrap();
+		let other_address = public_key_address(&secp256k1::PublicKey::from_secret_key(&other_secret));
+
+		let mut addresses = keystore_addresses(&keystore);
+		addresses.sort();
+		let mut expected = vec![cow(), other_address];
+		expected.sort();
+
+		assert_eq!(addresses, expected);
+		assert_eq!(keystore_addresses(&Store::new_in_memory()), Vec::<H160>::new());
+	}
+
+	#[test]
+	fn unknown_address_should_be_rejected() {
+		let api = signing_api();
+
+		let err = api.sign(H160::repeat_byte(0x11), Bytes(vec![1, 2, 3])).unwrap_err();
+
+		assert_eq!(err.code, jsonrpc_core::ErrorCode::InvalidParams);
+	}
+
+	#[test]
+	fn typed_data_should_hash_to_eip712_example() {
+		assert_eq!(
+			eip712::hash_typed_data(&mail()).unwrap(),
+			"be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2".parse().unwrap()
+		);
+	}
+
+	#[test]
+	fn signed_typed_data_should_recover_to_signer() {
+		let api = signing_api();
+		let hash = eip712::hash_typed_data(&mail()).unwrap();
+
+		let signature = api.sign_typed_data(cow(), mail()).unwrap();
+
+		assert_eq!(recover(hash, signature), cow());
+	}
+}
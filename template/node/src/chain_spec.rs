@@ -22,9 +22,10 @@ use frontier_template_runtime::{
 	SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
+use sp_core::storage::well_known_keys;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
-use sp_runtime::traits::{BlakeTwo256, IdentifyAccount, Verify};
+use sp_runtime::{BuildStorage, traits::{BlakeTwo256, IdentifyAccount, Verify}};
 use std::collections::BTreeMap;
 // Note this is the URL for the telemetry server
 //const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -32,6 +33,33 @@ use std::collections::BTreeMap;
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
 
+/// Magic bytes every wasm module starts with.
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Ensure the runtime code is a valid wasm module, so that a stale, empty or corrupted
+/// runtime fails at startup instead of on the first block execution. Runtimes of this
+/// Substrate version are stored uncompressed, so the code is parsed as is.
+pub fn check_runtime_code(code: &[u8]) -> Result<(), String> {
+	if code.is_empty() {
+		return Err("Runtime code is empty, make sure the runtime wasm was built".into());
+	}
+	if !code.starts_with(WASM_MAGIC) {
+		return Err("Runtime code is not a valid wasm module".into());
+	}
+	wasmi::Module::from_buffer(code)
+		.map_err(|e| format!("Runtime code is not a valid wasm module: {}", e))?;
+	Ok(())
+}
+
+/// Ensure the genesis runtime code of `spec` is valid, whether the spec was built here
+/// or loaded from a file.
+pub fn check_genesis_code(spec: &dyn sc_service::ChainSpec) -> Result<(), String> {
+	let storage = spec.as_storage_builder().build_storage()?;
+	let code = storage.top.get(well_known_keys::CODE)
+		.ok_or_else(|| "Genesis has no runtime code".to_string())?;
+	check_runtime_code(code)
+}
+
 /// Helper function to generate a crypto pair from seed
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
 	TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -54,8 +82,10 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
-pub fn development_config() -> ChainSpec {
-	ChainSpec::from_genesis(
+pub fn development_config() -> Result<ChainSpec, String> {
+	check_runtime_code(WASM_BINARY)?;
+
+	Ok(ChainSpec::from_genesis(
 		"Development",
 		"dev",
 		ChainType::Development,
@@ -77,11 +107,13 @@ pub fn development_config() -> ChainSpec {
 		None,
 		None,
 		None,
-	)
+	))
 }
 
-pub fn local_testnet_config() -> ChainSpec {
-	ChainSpec::from_genesis(
+pub fn local_testnet_config() -> Result<ChainSpec, String> {
+	check_runtime_code(WASM_BINARY)?;
+
+	Ok(ChainSpec::from_genesis(
 		"Local Testnet",
 		"local_testnet",
 		ChainType::Local,
@@ -114,7 +146,7 @@ pub fn local_testnet_config() -> ChainSpec {
 		None,
 		None,
 		None,
-	)
+	))
 }
 
 fn testnet_genesis(
@@ -164,3 +196,22 @@ fn testnet_genesis(
 		}),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn empty_runtime_code_should_be_rejected() {
+		assert!(check_runtime_code(&[]).is_err());
+		assert!(check_runtime_code(b"not wasm").is_err());
+		assert!(check_runtime_code(b"\0asm\x01\0\0\0").is_ok());
+	}
+
+	#[test]
+	fn malformed_runtime_code_should_be_rejected() {
+		assert!(check_runtime_code(b"\0asm").is_err());
+		assert!(check_runtime_code(b"\0asm\x01\0\0\0\x01").is_err());
+		assert!(check_runtime_code(b"\0asm\x02\0\0\0").is_err());
+	}
+}
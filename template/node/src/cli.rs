@@ -25,4 +25,49 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Minimum gas price of Ethereum transactions accepted over RPC, enforced on top
+	/// of the chain minimum.
+	#[structopt(long = "min-gas-price", default_value = "0")]
+	pub min_gas_price: u64,
+
+	/// Accept Ethereum transactions over RPC while the node is still syncing.
+	#[structopt(long = "accept-transactions-while-syncing")]
+	pub accept_transactions_while_syncing: bool,
+
+	/// Do not register the Ethereum RPC methods this node does not support, such as the
+	/// mining and compiler methods, instead of answering them with errors.
+	#[structopt(long = "eth-hide-unsupported-methods")]
+	pub eth_hide_unsupported_methods: bool,
+
+	/// Abort `eth_call` and `eth_estimateGas` executions running longer than this many
+	/// milliseconds.
+	#[structopt(long = "eth-execution-timeout")]
+	pub eth_execution_timeout: Option<u64>,
+
+	/// Maximum number of threads running `eth_call` and `eth_estimateGas` executions when
+	/// `--eth-execution-timeout` is set. Aborted executions keep their thread until they
+	/// complete.
+	#[structopt(long = "eth-execution-threads", default_value = "4")]
+	pub eth_execution_threads: usize,
+
+	/// Maximum number of calls in a single JSON-RPC batch. Larger batches are rejected
+	/// as a whole.
+	#[structopt(long = "rpc-max-batch-size", default_value = "1024")]
+	pub rpc_max_batch_size: usize,
+
+	/// Maximum number of `eth_call`, `eth_estimateGas` and `eth_getLogs` requests running
+	/// at once. Further requests fail with a "server busy" error.
+	#[structopt(long = "eth-request-limit")]
+	pub eth_request_limit: Option<usize>,
+
+	/// Percentage of the gas used by the dry run that `eth_estimateGas` adds to its
+	/// estimates.
+	#[structopt(long = "eth-gas-estimate-margin", default_value = "0")]
+	pub eth_gas_estimate_margin: u32,
+
+	/// Largest init code, in bytes, of the contract creations `eth_call` and
+	/// `eth_estimateGas` execute.
+	#[structopt(long = "eth-max-init-code-size", default_value = "49152")]
+	pub eth_max_init_code_size: usize,
 }
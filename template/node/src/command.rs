@@ -50,13 +50,15 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> Result<Box<dyn sc_service::ChainSpec>, String> {
-		Ok(match id {
-			"dev" => Box::new(chain_spec::development_config()),
-			"" | "local" => Box::new(chain_spec::local_testnet_config()),
+		let spec: Box<dyn sc_service::ChainSpec> = match id {
+			"dev" => Box::new(chain_spec::development_config()?),
+			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
 			path => Box::new(chain_spec::ChainSpec::from_json_file(
 				std::path::PathBuf::from(path),
 			)?),
-		})
+		};
+		chain_spec::check_genesis_code(&*spec)?;
+		Ok(spec)
 	}
 }
 
@@ -67,13 +69,28 @@ pub fn run() -> sc_cli::Result<()> {
 	match &cli.subcommand {
 		Some(subcommand) => {
 			let runner = cli.create_runner(subcommand)?;
-			runner.run_subcommand(subcommand, |config| Ok(new_full_start!(config).0))
+			runner.run_subcommand(subcommand, |config| Ok(
+				new_full_start!(config, Default::default(), Default::default(), Default::default()).0
+			))
 		}
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let submission = frontier_rpc::SubmissionConfig {
+				min_gas_price: cli.min_gas_price.into(),
+				accept_while_syncing: cli.accept_transactions_while_syncing,
+			};
+			let eth_config = crate::rpc::EthRpcConfig {
+				register_unsupported_methods: !cli.eth_hide_unsupported_methods,
+				execution_timeout: cli.eth_execution_timeout.map(std::time::Duration::from_millis),
+				execution_threads: cli.eth_execution_threads,
+				max_batch_size: cli.rpc_max_batch_size,
+				request_limit: cli.eth_request_limit,
+				gas_estimate_margin: cli.eth_gas_estimate_margin,
+				max_init_code_size: cli.eth_max_init_code_size,
+			};
 			runner.run_node(
 				service::new_light,
-				service::new_full,
+				|config| service::new_full(config, submission, eth_config),
 				frontier_template_runtime::VERSION
 			)
 		}
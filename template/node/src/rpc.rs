@@ -17,7 +17,7 @@
 
 //! A collection of node-specific RPC methods.
 
-use std::{sync::Arc, fmt};
+use std::{sync::{Arc, atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}}, fmt, time::Duration};
 
 use frontier_template_runtime::{Hash, AccountId, Index, opaque::Block, Balance, UncheckedExtrinsic};
 use sp_api::ProvideRuntimeApi;
@@ -25,9 +25,12 @@ use sp_transaction_pool::TransactionPool;
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_consensus::SelectChain;
 use sc_rpc_api::DenyUnsafe;
-use sc_client_api::backend::{StorageProvider, Backend, StateBackend};
+use sc_keystore::KeyStorePtr;
+use frontier_rpc::{BatchSizeLimit, NetworkStatusProvider, SubmissionConfig, SyncStatusProvider};
+use sc_client_api::{BlockchainEvents, backend::{StorageProvider, Backend, StateBackend}};
 use sp_runtime::traits::BlakeTwo256;
 use sp_block_builder::BlockBuilder;
+use jsonrpc_pubsub::manager::SubscriptionManager;
 
 /// Light client extra dependencies.
 pub struct LightDeps<C, F, P> {
@@ -53,15 +56,116 @@ pub struct FullDeps<C, P, SC> {
 	pub deny_unsafe: DenyUnsafe,
 	/// The Node authority flag
 	pub is_authority: bool,
+	/// Ethereum RPC settings chosen by the node operator
+	pub eth_config: EthRpcConfig,
+	/// The keystore holding keys used by the Ethereum signing methods
+	pub keystore: KeyStorePtr,
+	/// Latest sync state and connectivity reported by the network
+	pub sync_status: Arc<NetworkSyncStatus>,
+	/// Checks applied to Ethereum transactions submitted over RPC
+	pub submission: SubmissionConfig,
+	/// Whether the node keeps the state of every block
+	pub archive: bool,
+	/// Manager of the Ethereum pub-sub subscriptions
+	pub subscriptions: SubscriptionManager,
 }
 
-/// Instantiate all Full RPC extensions.
-pub fn create_full<C, P, M, SC, BE>(
+/// Ethereum RPC settings chosen by the node operator.
+#[derive(Clone, Debug)]
+pub struct EthRpcConfig {
+	/// Whether to register Ethereum RPC methods this node does not support, which only
+	/// ever return errors.
+	pub register_unsupported_methods: bool,
+	/// Deadline of `eth_call` and `eth_estimateGas` executions, if any.
+	pub execution_timeout: Option<Duration>,
+	/// Number of threads executions with a deadline may run on.
+	pub execution_threads: usize,
+	/// Maximum number of calls in a single JSON-RPC batch.
+	pub max_batch_size: usize,
+	/// Maximum number of `eth_call`, `eth_estimateGas` and `eth_getLogs` requests running
+	/// at once, if any.
+	pub request_limit: Option<usize>,
+	/// Percentage of the gas used by the dry run added to `eth_estimateGas` results.
+	pub gas_estimate_margin: u32,
+	/// Largest init code, in bytes, that `eth_call` and `eth_estimateGas` execute.
+	pub max_init_code_size: usize,
+}
+
+impl Default for EthRpcConfig {
+	fn default() -> Self {
+		Self {
+			register_unsupported_methods: true,
+			execution_timeout: None,
+			execution_threads: 4,
+			max_batch_size: frontier_rpc::DEFAULT_MAX_BATCH_SIZE,
+			request_limit: None,
+			gas_estimate_margin: 0,
+			max_init_code_size: frontier_rpc::MAX_INIT_CODE_SIZE,
+		}
+	}
+}
+
+/// Sync state and connectivity of the node, updated from the network status stream
+/// once the network has been started.
+#[derive(Default)]
+pub struct NetworkSyncStatus {
+	syncing: AtomicBool,
+	starting_block: AtomicU64,
+	highest_block: AtomicU64,
+	peers: AtomicUsize,
+	listening: AtomicBool,
+}
+
+impl NetworkSyncStatus {
+	/// Record whether the node is syncing, given its best block and the highest block
+	/// seen from peers. The best block is remembered as the start of a new sync.
+	pub fn update(&self, syncing: bool, best_block: u64, highest_seen: Option<u64>) {
+		if syncing && !self.syncing.swap(true, Ordering::Relaxed) {
+			self.starting_block.store(best_block, Ordering::Relaxed);
+		} else if !syncing {
+			self.syncing.store(false, Ordering::Relaxed);
+		}
+		self.highest_block.store(highest_seen.unwrap_or(best_block).max(best_block), Ordering::Relaxed);
+	}
+
+	/// Record the number of connected peers. Status is only reported by a running
+	/// network, so the node is listening from then on.
+	pub fn update_peers(&self, peers: usize) {
+		self.peers.store(peers, Ordering::Relaxed);
+		self.listening.store(true, Ordering::Relaxed);
+	}
+}
+
+impl NetworkStatusProvider for NetworkSyncStatus {
+	fn peer_count(&self) -> usize {
+		self.peers.load(Ordering::Relaxed)
+	}
+
+	fn is_listening(&self) -> bool {
+		self.listening.load(Ordering::Relaxed)
+	}
+}
+
+impl SyncStatusProvider for NetworkSyncStatus {
+	fn is_major_syncing(&self) -> bool {
+		self.syncing.load(Ordering::Relaxed)
+	}
+
+	fn sync_range(&self) -> Option<(u64, u64)> {
+		Some((
+			self.starting_block.load(Ordering::Relaxed),
+			self.highest_block.load(Ordering::Relaxed),
+		))
+	}
+}
+
+/// Instantiate all Full RPC extensions, rejecting batches larger than configured.
+pub fn create_full<C, P, SC, BE>(
 	deps: FullDeps<C, P, SC>,
-) -> jsonrpc_core::IoHandler<M> where
+) -> jsonrpc_core::MetaIoHandler<sc_rpc::Metadata, BatchSizeLimit> where
 	BE: Backend<Block> + 'static,
 	BE::State: StateBackend<BlakeTwo256>,
-	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE>,
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + BlockchainEvents<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
@@ -69,22 +173,32 @@ pub fn create_full<C, P, M, SC, BE>(
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance, UncheckedExtrinsic>,
 	C::Api: frontier_rpc_primitives::EthereumRuntimeApi<Block>,
 	<C::Api as sp_api::ApiErrorExt>::Error: fmt::Debug,
-	P: TransactionPool<Block=Block> + 'static,
-	M: jsonrpc_core::Metadata + Default,
+	P: TransactionPool<Block=Block> + frontier_rpc::FutureTransactions<Block> + 'static,
 	SC: SelectChain<Block> +'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
-	use frontier_rpc::{EthApi, EthApiServer};
+	use frontier_rpc::{
+		EthApi, EthApiServer, EthPubSubApi, EthPubSubApiServer, EthSigningApi, EthSigningApiServer,
+		NetApiServer, TxPoolApiServer, without_unsupported_methods,
+	};
 
-	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
 		client,
 		pool,
 		select_chain,
 		deny_unsafe,
-		is_authority
+		is_authority,
+		eth_config,
+		keystore,
+		sync_status,
+		submission,
+		archive,
+		subscriptions,
 	} = deps;
+	let mut io = jsonrpc_core::MetaIoHandler::with_middleware(
+		BatchSizeLimit::new(eth_config.max_batch_size)
+	);
 
 	io.extend_with(
 		SystemApi::to_delegate(FullSystem::new(client.clone(), pool.clone(), deny_unsafe))
@@ -92,15 +206,70 @@ pub fn create_full<C, P, M, SC, BE>(
 	io.extend_with(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
+	let mut eth = EthApi::new(
+		client.clone(),
+		select_chain.clone(),
+		pool.clone(),
+		frontier_template_runtime::TransactionConverter,
+		is_authority,
+		sync_status.clone(),
+		submission.clone(),
+	);
+	if archive {
+		eth = eth.with_archive_state();
+	}
+	if let Some(timeout) = eth_config.execution_timeout {
+		eth = eth.with_execution_timeout(timeout, eth_config.execution_threads);
+	}
+	if let Some(limit) = eth_config.request_limit {
+		eth = eth.with_request_limit(limit);
+	}
+	eth = eth.with_gas_estimate_margin(eth_config.gas_estimate_margin)
+		.with_max_init_code_size(eth_config.max_init_code_size);
+	// Like signing, listing the node's accounts is only offered over unsafe RPC.
+	if deny_unsafe.check_if_safe().is_ok() {
+		eth = eth.with_keystore(keystore.clone());
+	}
+	let eth = EthApiServer::to_delegate(eth);
+	if eth_config.register_unsupported_methods {
+		io.extend_with(eth);
+	} else {
+		io.extend_with(without_unsupported_methods(eth));
+	}
+	// Signing exposes keys held by the node, so it is only offered over unsafe RPC.
+	if deny_unsafe.check_if_safe().is_ok() {
+		let signing = EthSigningApiServer::to_delegate(EthSigningApi::new(keystore));
+		if eth_config.register_unsupported_methods {
+			io.extend_with(signing);
+		} else {
+			io.extend_with(without_unsupported_methods(signing));
+		}
+	}
+	io.extend_with(
+		NetApiServer::to_delegate(EthApi::new(
+			client.clone(),
+			select_chain.clone(),
+			pool.clone(),
+			frontier_template_runtime::TransactionConverter,
+			is_authority,
+			sync_status.clone(),
+			submission.clone(),
+		).with_network(sync_status.clone()))
+	);
 	io.extend_with(
-		EthApiServer::to_delegate(EthApi::new(
+		TxPoolApiServer::to_delegate(EthApi::new(
 			client.clone(),
 			select_chain,
 			pool.clone(),
 			frontier_template_runtime::TransactionConverter,
 			is_authority,
+			sync_status,
+			submission,
 		))
 	);
+	io.extend_with(
+		EthPubSubApiServer::to_delegate(EthPubSubApi::new(client.clone(), subscriptions))
+	);
 
 	io
 }
@@ -24,6 +24,11 @@ use sc_consensus::LongestChain;
 use frontier_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_service::{error::{Error as ServiceError}, AbstractService, Configuration, ServiceBuilder};
 use sp_inherents::InherentDataProviders;
+use sc_network::SyncState;
+use sp_blockchain::HeaderBackend;
+use futures::{future, StreamExt};
+use frontier_rpc::SubmissionConfig;
+use crate::rpc::{EthRpcConfig, NetworkSyncStatus};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
@@ -43,7 +48,7 @@ native_executor_instance!(
 /// Use this macro if you don't actually need the full service, but just the builder in order to
 /// be able to perform chain operations.
 macro_rules! new_full_start {
-	($config:expr) => {{
+	($config:expr, $sync_status:expr, $submission:expr, $eth_config:expr) => {{
 		use std::sync::Arc;
 		use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
 
@@ -106,8 +111,18 @@ macro_rules! new_full_start {
 				let client = builder.client().clone();
 				let is_authority: bool = builder.config().role.is_authority();
 				let pool = builder.pool().clone();
+				let keystore = builder.keystore();
+				let sync_status: Arc<crate::rpc::NetworkSyncStatus> = $sync_status;
+				let submission: frontier_rpc::SubmissionConfig = $submission;
+				let eth_config: crate::rpc::EthRpcConfig = $eth_config;
+				let archive = match &builder.config().pruning {
+					sc_service::config::PruningMode::ArchiveAll |
+					sc_service::config::PruningMode::ArchiveCanonical => true,
+					sc_service::config::PruningMode::Constrained(_) => false,
+				};
 				let select_chain = builder.select_chain().cloned()
 					.expect("SelectChain is present for full services or set up failed; qed.");
+				let subscription_executor = sc_rpc::SubscriptionTaskExecutor::new(builder.spawn_handle());
 
 				Ok(move |deny_unsafe| {
 					let deps = crate::rpc::FullDeps {
@@ -115,7 +130,15 @@ macro_rules! new_full_start {
 						pool: pool.clone(),
 						select_chain: select_chain.clone(),
 						deny_unsafe,
-						is_authority
+						is_authority,
+						eth_config: eth_config.clone(),
+						keystore: keystore.clone(),
+						sync_status: sync_status.clone(),
+						submission: submission.clone(),
+						archive,
+						subscriptions: jsonrpc_pubsub::manager::SubscriptionManager::new(
+							Arc::new(subscription_executor.clone())
+						),
 					};
 
 					crate::rpc::create_full(deps)
@@ -126,14 +149,21 @@ macro_rules! new_full_start {
 	}}
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<impl AbstractService, ServiceError> {
+/// Builds a new service for a full client. `submission` configures the checks applied
+/// to Ethereum transactions submitted over RPC, `eth_config` the Ethereum RPC itself.
+pub fn new_full(
+	config: Configuration,
+	submission: SubmissionConfig,
+	eth_config: EthRpcConfig,
+) -> Result<impl AbstractService, ServiceError> {
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
 	let name = config.network.node_name.clone();
 	let disable_grandpa = config.disable_grandpa;
 
-	let (builder, mut import_setup, inherent_data_providers) = new_full_start!(config);
+	let sync_status = Arc::new(NetworkSyncStatus::default());
+	let (builder, mut import_setup, inherent_data_providers) =
+		new_full_start!(config, sync_status.clone(), submission, eth_config);
 
 	let (block_import, grandpa_link) =
 		import_setup.take()
@@ -147,6 +177,18 @@ pub fn new_full(config: Configuration) -> Result<impl AbstractService, ServiceEr
 		})?
 		.build_full()?;
 
+	let network_status = service.network_status(Duration::from_secs(1));
+	let status_client = service.client();
+	service.spawn_task("eth-sync-status", network_status.for_each(move |(status, _)| {
+		sync_status.update(
+			status.sync_state == SyncState::Downloading,
+			status_client.info().best_number.into(),
+			status.best_seen_block.map(Into::into),
+		);
+		sync_status.update_peers(status.num_connected_peers);
+		future::ready(())
+	}));
+
 	if role.is_authority() {
 		let proposer = sc_basic_authorship::ProposerFactory::new(
 			service.client(),
@@ -32,14 +32,14 @@ use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata, U256, H160, H256};
 use sp_runtime::traits::{
-	BlakeTwo256, Block as BlockT, IdentifyAccount, IdentityLookup, NumberFor, Saturating, Verify,
+	BlakeTwo256, Block as BlockT, Convert, IdentifyAccount, IdentityLookup, NumberFor, Saturating, Verify,
 };
 use sp_runtime::{
-	create_runtime_str, generic, impl_opaque_keys,
+	create_runtime_str, generic, impl_opaque_keys, ConsensusEngineId,
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, ModuleId, MultiSignature,
 };
-use sp_std::prelude::*;
+use sp_std::{prelude::*, marker::PhantomData};
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
@@ -50,7 +50,7 @@ pub use balances::Call as BalancesCall;
 pub use evm::Account as EVMAccount;
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{KeyOwnerProofSystem, Randomness, FindAuthor},
+	traits::{KeyOwnerProofSystem, Randomness, FindAuthor, Get},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
@@ -58,7 +58,7 @@ pub use frame_support::{
 	StorageValue,
 };
 use ethereum::{Block as EthereumBlock, Transaction as EthereumTransaction};
-use frontier_rpc_primitives::{TransactionStatus};
+use frontier_rpc_primitives::{TransactionStatus, ExecutionError};
 
 
 #[cfg(any(feature = "std", test))]
@@ -121,7 +121,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-frontier-template"),
 	impl_name: create_runtime_str!("node-frontier-template"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -304,9 +304,46 @@ impl evm::Trait for Runtime {
 	type Precompiles = ();
 }
 
+/// Ethereum block author, derived from the author found by `F`.
+pub struct EthereumFindAuthor<F>(PhantomData<F>);
+
+impl<F: FindAuthor<u32>> FindAuthor<H160> for EthereumFindAuthor<F> {
+	fn find_author<'a, I>(digests: I) -> Option<H160> where
+		I: 'a + IntoIterator<Item=(ConsensusEngineId, &'a [u8])>
+	{
+		if let Some(author_index) = F::find_author(digests) {
+			let authority_id = &Aura::authorities()[author_index as usize];
+			return Some(HashTruncateConvertAccountId::<BlakeTwo256>::convert_account_id(authority_id));
+		}
+		None
+	}
+}
+
+parameter_types! {
+	/// Keep the transaction statuses of the last week in the state of every block. Archive
+	/// nodes still serve older ones from the state of the block that stored them.
+	pub const TransactionStatusRetention: BlockNumber = 7 * DAYS;
+	pub BlockGasLimit: U256 = U256::from(15_000_000);
+}
+
+pub struct EvmEvents;
+
+impl Convert<Event, Option<evm::Event<Runtime>>> for EvmEvents {
+	fn convert(event: Event) -> Option<evm::Event<Runtime>> {
+		match event {
+			Event::evm(event) => Some(event),
+			_ => None,
+		}
+	}
+}
+
 impl ethereum::Trait for Runtime {
 	type Event = Event;
 	type ChainId = ChainId;
+	type TransactionStatusRetention = TransactionStatusRetention;
+	type FindAuthor = EthereumFindAuthor<Aura>;
+	type BlockGasLimit = BlockGasLimit;
+	type EvmEvents = EvmEvents;
 }
 
 construct_runtime!(
@@ -463,28 +500,27 @@ impl_runtime_apis! {
 		}
 
 		fn gas_price() -> U256 {
-			FixedGasPrice::min_gas_price()
+			<ethereum::Module<Runtime>>::gas_price()
 		}
 
 		fn account_code_at(address: H160) -> Vec<u8> {
 			evm::Module::<Runtime>::account_codes(address)
 		}
 
+		fn account_code_len(address: H160) -> u64 {
+			<ethereum::Module<Runtime>>::account_code_len(address)
+		}
+
 		fn author() -> H160 {
-			let digest = <system::Module<Runtime>>::digest();
-			let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
-			if let Some(index) = <aura::Module<Runtime>>::find_author(pre_runtime_digests) {
-				let authority_id = &<aura::Module<Runtime>>::authorities()[index as usize];
-				<evm::HashTruncateConvertAccountId<BlakeTwo256>>::convert_account_id(&authority_id)
-			} else {
-				H160::zero()
-			}
+			<ethereum::Module<Runtime>>::find_author()
 		}
 
 		fn storage_at(address: H160, index: U256) -> H256 {
-			let mut tmp = [0u8; 32];
-			index.to_big_endian(&mut tmp);
-			evm::Module::<Runtime>::account_storages(address, H256::from_slice(&tmp[..]))
+			<ethereum::Module<Runtime>>::storage_at(address, index)
+		}
+
+		fn substrate_account(address: H160) -> Option<AccountId> {
+			<ethereum::Module<Runtime>>::substrate_account(address)
 		}
 
 		fn call(
@@ -495,41 +531,45 @@ impl_runtime_apis! {
 			gas_limit: U256,
 			gas_price: U256,
 			nonce: Option<U256>,
-		) -> Option<(Vec<u8>, U256)> {
-			evm::Module::<Runtime>::execute_call(
-				from,
-				to,
-				data,
-				value,
-				gas_limit.low_u32(),
-				gas_price,
-				nonce,
-				false,
-			).ok().map(|(_, ret, gas)| (ret, gas))
+		) -> Result<(Vec<u8>, U256), ExecutionError> {
+			<ethereum::Module<Runtime>>::call(from, to, data, value, gas_limit, gas_price, nonce)
+		}
+
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: U256,
+			nonce: Option<U256>,
+		) -> Result<(Vec<u8>, U256), ExecutionError> {
+			<ethereum::Module<Runtime>>::create(from, data, value, gas_limit, gas_price, nonce)
 		}
 
 		fn block_by_number(number: u32) -> Option<EthereumBlock> {
 			<ethereum::Module<Runtime>>::block_by_number(number)
 		}
 
+		fn blocks_in_range(from: u32, to: u32) -> Vec<EthereumBlock> {
+			<ethereum::Module<Runtime>>::blocks_in_range(from, to)
+		}
+
 		fn block_transaction_count_by_number(number: u32) -> Option<U256> {
-			if let Some(block) = <ethereum::Module<Runtime>>::block_by_number(number) {
-				return Some(U256::from(block.transactions.len()))
-			}
-			None
+			<ethereum::Module<Runtime>>::block_transaction_count_by_number(number)
 		}
 
 		fn block_transaction_count_by_hash(hash: H256) -> Option<U256> {
-			if let Some(block) = <ethereum::Module<Runtime>>::block_by_hash(hash) {
-				return Some(U256::from(block.transactions.len()))
-			}
-			None
+			<ethereum::Module<Runtime>>::block_transaction_count_by_hash(hash)
 		}
 
 		fn block_by_hash(hash: H256) -> Option<EthereumBlock> {
 			<ethereum::Module<Runtime>>::block_by_hash(hash)
 		}
 
+		fn block_transaction_hashes(hash: H256) -> Option<Vec<H256>> {
+			<ethereum::Module<Runtime>>::block_transaction_hashes(hash)
+		}
+
 		fn transaction_by_hash(hash: H256) -> Option<(
 			EthereumTransaction,
 			EthereumBlock,
@@ -553,6 +593,19 @@ impl_runtime_apis! {
 				index
 			)
 		}
+
+		fn extrinsic_filter(
+			xts: Vec<<Block as BlockT>::Extrinsic>
+		) -> Vec<EthereumTransaction> {
+			xts.into_iter().filter_map(|xt| match xt.function {
+				Call::Ethereum(ethereum::Call::transact(transaction)) => Some(transaction),
+				_ => None
+			}).collect()
+		}
+
+		fn transaction_status_retention() -> u32 {
+			<ethereum::Module<Runtime>>::transaction_status_retention()
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<